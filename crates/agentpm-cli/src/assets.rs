@@ -1,2 +1,6 @@
 pub const TOOL_AGENT_JSON_TPL: &str = include_str!("../assets/templates/tool_agent.json.tpl");
 pub const AGENT_JSON_TPL: &str = include_str!("../assets/templates/agent.json.tpl");
+/// The manifest schema this binary was built with, for `lint` when it can't fetch one.
+pub const MANIFEST_SCHEMA: &str = include_str!("../../../schemas/agentpm.manifest.schema.json");
+/// The draft 2020-12 meta-schema as one document, `$dynamicRef`s resolved to `$ref`s.
+pub const META_SCHEMA_2020_12: &str = include_str!("../assets/json-schema-2020-12.json");
@@ -0,0 +1,69 @@
+use crate::commands::lock::relock;
+use crate::manifest::{self, ManifestKind, ToolRef};
+use crate::prelude::*;
+use crate::project::Project;
+use crate::resolve::{ResolveOptions, Resolver, Update};
+use anyhow::bail;
+use std::fs;
+
+#[derive(Args, Debug)]
+pub struct AddArgs {
+    /// Tools to add, e.g. `summarize` or `summarize@^1.2`
+    #[arg(value_name = "NAME[@RANGE]", required = true)]
+    tools: Vec<String>,
+}
+
+impl AddArgs {
+    pub async fn run(self, base_url: String) -> Result<()> {
+        let cfg = Config::load(base_url)?;
+        let project = Project::discover()?;
+        let client = AgentPmClient::new(cfg.base_url.clone())?;
+
+        if project.manifest.kind == ManifestKind::Tool {
+            bail!(
+                "{} is a `kind: tool` manifest; only agents can compose tools",
+                project.manifest_path().display()
+            );
+        }
+
+        let path = project.manifest_path();
+        let mut text =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        let mut resolver = Resolver::new(&client, None, ResolveOptions::default());
+        let mut done = Vec::new();
+
+        for spec in &self.tools {
+            let spec = ToolRef::Short(spec.clone());
+            let name = spec.name();
+            if name.is_empty() {
+                bail!("Missing tool name in `{spec}`");
+            }
+            manifest::check_name(name)?;
+            if name == project.manifest.name {
+                bail!("`{name}` can't depend on itself");
+            }
+            let req = spec.version_req()?;
+
+            // Make sure the registry has something that satisfies the range
+            let Some(newest) = resolver.newest(name, &req).await? else {
+                bail!("No published version of `{name}` matches `{req}`");
+            };
+
+            let range = match spec.range() {
+                Some(range) => range.to_string(),
+                None => format!("^{newest}"),
+            };
+            text = manifest::set_tool(&text, name, &range)?;
+            done.push(format!("Added {name}@{range}"));
+        }
+
+        let (lock, previous) = relock(&project, &text, &client, Update::Nothing).await?;
+        for line in done {
+            println!("{line}");
+        }
+        for change in lock.changes_from(previous.as_ref()) {
+            println!("{change}");
+        }
+        Ok(())
+    }
+}
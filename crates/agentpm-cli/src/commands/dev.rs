@@ -0,0 +1,216 @@
+use crate::commands::run::RunLimitArgs;
+use crate::commands::test;
+use crate::env::DOTENV_FILE;
+use crate::lint::{self, Catalog, FixMode, LintConfig, Linter, SchemaCache};
+use crate::manifest::{MANIFEST_FILE, Manifest, ManifestKind};
+use crate::prelude::*;
+use crate::project::Project;
+use crate::suite::run_suite;
+use ignore::WalkBuilder;
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecursiveMode, Watcher},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+
+/// Directories whose contents never trigger a re-run: installs, VCS data,
+/// dependency and build output the tool itself may write.
+const IGNORED_DIRS: &[&str] = &[
+    ".agentpm",
+    ".git",
+    "node_modules",
+    "__pycache__",
+    ".venv",
+    "venv",
+    "target",
+];
+
+#[derive(Args, Debug)]
+pub struct DevArgs {
+    /// Wait this long after the last change before re-running
+    #[arg(long, value_name = "MS", default_value_t = 200)]
+    debounce: u64,
+
+    /// Keep earlier output instead of clearing the screen on each run
+    #[arg(long)]
+    no_clear: bool,
+
+    /// Override schema URL or path used by lint
+    #[arg(long, value_name = "URL|PATH")]
+    schema: Option<String>,
+
+    #[command(flatten)]
+    limits: RunLimitArgs,
+}
+
+impl DevArgs {
+    pub async fn run(self, base_url: String) -> Result<()> {
+        let root = Project::discover()?.root;
+        let config = LintConfig::discover(&root)?;
+        let schemas = SchemaCache::new(&Config::load(base_url)?, false);
+        let linter = Linter::new(self.schema.clone(), &schemas, config, false, FixMode::Off)?;
+
+        let (tx, rx) = mpsc::channel();
+        let mut debouncer = new_debouncer(Duration::from_millis(self.debounce), tx)
+            .context("starting the file watcher")?;
+        let mut watched = BTreeSet::new();
+        sync_watches(debouncer.watcher(), &root, &mut watched)?;
+
+        let mut changed: Vec<PathBuf> = Vec::new();
+        loop {
+            // Taken first, so saves made while lint and the tests run count as changes
+            let before = snapshot(&root);
+            self.cycle(&root, &linter, &changed);
+
+            // Watcher events only wake us up; reading files (as lint and the tests do)
+            // produces events too, so compare snapshots to see what really changed
+            changed = loop {
+                match rx.recv() {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => {
+                        eprintln!("file watcher: {e}");
+                        continue;
+                    }
+                    Err(_) => return Ok(()),
+                }
+                // Directories come and go; new ones need watches of their own
+                if let Err(e) = sync_watches(debouncer.watcher(), &root, &mut watched) {
+                    eprintln!("file watcher: {e:#}");
+                }
+                let after = snapshot(&root);
+                let diff: Vec<PathBuf> = before
+                    .keys()
+                    .chain(after.keys())
+                    .filter(|p| before.get(*p) != after.get(*p))
+                    .cloned()
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect();
+                if !diff.is_empty() {
+                    break diff;
+                }
+            };
+        }
+    }
+
+    /// Lint the manifest, then run the test cases if it is a tool.
+    fn cycle(&self, root: &Path, linter: &Linter, changed: &[PathBuf]) {
+        if !self.no_clear && std::io::stdout().is_terminal() {
+            print!("\x1b[2J\x1b[H");
+        }
+        if !changed.is_empty() {
+            let names: Vec<String> = changed
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap_or(p).display().to_string())
+                .collect();
+            println!("changed: {}\n", names.join(", "));
+        }
+
+        // Tool references are checked against local sources only; the registry is too slow
+        // to ask on every save
+        let manifest = root.join(MANIFEST_FILE);
+        let catalog = Catalog::discover(std::slice::from_ref(&manifest));
+        match linter.lint_file(&manifest, &catalog) {
+            Ok(report) => lint::print_pretty(&[report]),
+            Err(e) => println!("✗ lint: {e:#}"),
+        }
+        let project = match Project::discover() {
+            Ok(project) => project,
+            Err(e) => {
+                println!("✗ {e:#}");
+                return footer();
+            }
+        };
+        if project.manifest.kind != ManifestKind::Tool {
+            return footer();
+        }
+        println!();
+
+        let report = self
+            .limits
+            .options(&project)
+            .and_then(|opts| run_suite(&project, None, &opts));
+        match report {
+            Ok(report) => test::print_pretty(&report),
+            Err(e) => println!("✗ tests: {e:#}"),
+        }
+        footer();
+    }
+}
+
+/// Modification time and size of every project file, skipping `IGNORED_DIRS` and
+/// whatever .gitignore excludes (but always including agent.json, `.env` and the
+/// entrypoint, which may well be a build output under `target/`).
+fn snapshot(root: &Path) -> BTreeMap<PathBuf, (Option<SystemTime>, u64)> {
+    walk(root)
+        .map(|e| e.into_path())
+        .chain(always_watched(root))
+        .filter_map(|path| {
+            let meta = std::fs::metadata(&path).ok().filter(|m| m.is_file())?;
+            Some((path, (meta.modified().ok(), meta.len())))
+        })
+        .collect()
+}
+
+/// Project files and directories, minus `IGNORED_DIRS` and what .gitignore excludes.
+fn walk(root: &Path) -> impl Iterator<Item = ignore::DirEntry> {
+    WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|e| !IGNORED_DIRS.iter().any(|d| e.file_name() == *d))
+        .build()
+        .filter_map(|e| e.ok())
+}
+
+/// Files that count even inside ignored directories: agent.json, `.env` and the entrypoint.
+fn always_watched(root: &Path) -> Vec<PathBuf> {
+    let manifest = root.join(MANIFEST_FILE);
+    let entrypoint = Manifest::load(&manifest)
+        .ok()
+        .and_then(|m| m.entrypoint)
+        .map(|e| root.join(e));
+    [manifest, root.join(DOTENV_FILE)]
+        .into_iter()
+        .chain(entrypoint)
+        .collect()
+}
+
+/// Watch each walked directory on its own, so build output under ignored directories
+/// never reaches the debouncer, plus the directories of the always-watched files.
+fn sync_watches(
+    watcher: &mut dyn Watcher,
+    root: &Path,
+    watched: &mut BTreeSet<PathBuf>,
+) -> Result<()> {
+    let wanted: BTreeSet<PathBuf> = walk(root)
+        .filter(|e| e.file_type().is_some_and(|t| t.is_dir()))
+        .map(|e| e.into_path())
+        .chain(
+            always_watched(root)
+                .iter()
+                .filter_map(|f| f.parent().map(Path::to_path_buf)),
+        )
+        .filter(|dir| dir.is_dir())
+        .collect();
+    for gone in watched.difference(&wanted) {
+        // The directory is usually deleted already, and its watch with it
+        let _ = watcher.unwatch(gone);
+    }
+    for dir in wanted.difference(watched) {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("watching {}", dir.display()))?;
+    }
+    *watched = wanted;
+    Ok(())
+}
+
+fn footer() {
+    println!("\nwatching for changes (Ctrl-C to stop)");
+}
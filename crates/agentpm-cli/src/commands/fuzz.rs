@@ -0,0 +1,145 @@
+use crate::commands::OutputFormat;
+use crate::commands::run::RunLimitArgs;
+use crate::fuzz::{FuzzOptions, FuzzReport, fuzz};
+use crate::io::fs::write_atomic;
+use crate::prelude::*;
+use crate::project::Project;
+use crate::runner::ToolPackage;
+use crate::suite::TESTS_DIR;
+use anyhow::bail;
+use serde_json::json;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Args, Debug)]
+pub struct FuzzArgs {
+    /// Number of generated inputs to try
+    #[arg(long, value_name = "N", default_value_t = 100)]
+    runs: usize,
+
+    /// Seed for input generation; reuse the one printed by a failing run to reproduce it
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Tool runs to spend shrinking a failing input
+    #[arg(long, value_name = "N", default_value_t = 200)]
+    max_shrinks: usize,
+
+    /// Save the shrunk failing input as a golden case in tests/fuzz-<seed>.json
+    #[arg(long)]
+    save: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "pretty")]
+    format: OutputFormat,
+
+    #[command(flatten)]
+    limits: RunLimitArgs,
+}
+
+impl FuzzArgs {
+    pub async fn run(self) -> Result<()> {
+        let project = Project::discover()?;
+        let tool = ToolPackage::new(project.root.clone(), project.manifest.clone())?;
+        let run_opts = self.limits.options(&project)?;
+        let opts = FuzzOptions {
+            runs: self.runs,
+            seed: self.seed.unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_nanos() as u64)
+                    .unwrap_or_default()
+            }),
+            max_shrinks: self.max_shrinks,
+        };
+
+        let pretty = matches!(self.format, OutputFormat::Pretty);
+        if pretty {
+            println!(
+                "fuzzing `{}` with {} generated input(s) (seed {})",
+                tool.manifest.name, opts.runs, opts.seed
+            );
+        }
+        let report = fuzz(&tool, &opts, &run_opts, |ok| {
+            if pretty {
+                print!("{}", if ok { "." } else { "F" });
+                let _ = std::io::stdout().flush();
+            }
+        })?;
+
+        let saved = match &report.failure {
+            Some(failure) if self.save => {
+                let path = project
+                    .root
+                    .join(TESTS_DIR)
+                    .join(format!("fuzz-{}.json", report.seed));
+                let case = json!({
+                    "name": format!("fuzz-{}", report.seed),
+                    "input": failure.input,
+                });
+                write_atomic(&path, &(serde_json::to_string_pretty(&case)? + "\n"))?;
+                Some(path)
+            }
+            _ => None,
+        };
+
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            OutputFormat::Pretty => print_pretty(&report),
+        }
+        if let Some(path) = &saved {
+            eprintln!(
+                "saved the failing input to {} (`agentpm test` now reproduces it)",
+                path.strip_prefix(&project.root).unwrap_or(path).display()
+            );
+        }
+
+        if let Some(failure) = &report.failure {
+            bail!(
+                "`{}` failed on generated input #{} (reproduce with --seed {})",
+                report.tool,
+                failure.run,
+                report.seed
+            );
+        }
+        Ok(())
+    }
+}
+
+fn print_pretty(report: &FuzzReport) {
+    println!();
+    let Some(failure) = &report.failure else {
+        println!(
+            "fuzz result: ok. {} input(s) passed in {:.2}s",
+            report.runs,
+            report.duration.as_secs_f64()
+        );
+        return;
+    };
+
+    println!("✗ input #{} failed", failure.run);
+    if failure.shrink_steps > 0 {
+        println!("  shrunk in {} step(s) to:", failure.shrink_steps);
+    } else {
+        println!("  input:");
+    }
+    let input = serde_json::to_string_pretty(&failure.input).unwrap_or_default();
+    for line in input.lines() {
+        println!("    {line}");
+    }
+    println!("  problems:");
+    for problem in &failure.problems {
+        println!("    - {problem}");
+    }
+    if !failure.stderr.trim().is_empty() {
+        println!("  stderr:");
+        for line in failure.stderr.trim_end().lines() {
+            println!("    {line}");
+        }
+    }
+    println!(
+        "fuzz result: FAILED after {} input(s) in {:.2}s",
+        report.runs,
+        report.duration.as_secs_f64()
+    );
+}
@@ -0,0 +1,150 @@
+use crate::commands::OutputFormat;
+use crate::history::{History, RunRecord};
+use crate::prelude::*;
+
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    #[command(subcommand)]
+    command: HistoryCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum HistoryCommand {
+    /// List recorded runs, newest first
+    List(ListArgs),
+    /// Show everything recorded about one run
+    Show(ShowArgs),
+}
+
+#[derive(Args, Debug)]
+struct ListArgs {
+    /// Only runs of this tool
+    #[arg(long, value_name = "NAME")]
+    tool: Option<String>,
+
+    /// Show at most N runs
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    limit: usize,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "pretty")]
+    format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+struct ShowArgs {
+    /// Run id, as listed by `agentpm history list`
+    id: u64,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "pretty")]
+    format: OutputFormat,
+}
+
+impl HistoryArgs {
+    pub async fn run(self, base_url: String) -> Result<()> {
+        let cfg = Config::load(base_url)?;
+        let history = History::new(&cfg);
+        match self.command {
+            HistoryCommand::List(args) => {
+                let records: Vec<RunRecord> = history
+                    .list()?
+                    .into_iter()
+                    .filter(|r| args.tool.as_ref().is_none_or(|t| &r.tool == t))
+                    .take(args.limit)
+                    .collect();
+                match args.format {
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&records)?),
+                    OutputFormat::Pretty => print_list(&records),
+                }
+            }
+            HistoryCommand::Show(args) => {
+                let record = history.get(args.id)?;
+                match args.format {
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&record)?),
+                    OutputFormat::Pretty => print_record(&record)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn print_list(records: &[RunRecord]) {
+    if records.is_empty() {
+        println!("No recorded runs yet. `agentpm run` records every run.");
+        return;
+    }
+    let tool_width = records
+        .iter()
+        .map(|r| r.tool.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let id_width = records
+        .iter()
+        .map(|r| r.id.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max(2);
+    println!(
+        "{:>id_width$}  {:<8}  {:<tool_width$}  {:<10}  {:<6}  TIME",
+        "ID", "WHEN", "TOOL", "VERSION", "STATUS"
+    );
+    for r in records {
+        println!(
+            "{:>id_width$}  {:<8}  {:<tool_width$}  {:<10}  {:<6}  {} ms",
+            r.id,
+            r.age(),
+            r.tool,
+            r.version,
+            if r.problems.is_empty() {
+                "ok"
+            } else {
+                "failed"
+            },
+            r.duration_ms
+        );
+    }
+}
+
+fn print_record(r: &RunRecord) -> Result<()> {
+    println!("run #{}: {} {} ({})", r.id, r.tool, r.version, r.age());
+    println!("  project:  {}", r.project.display());
+    println!("  digest:   {}", r.digest);
+    match r.exit_code {
+        Some(code) => println!("  exit:     {code} after {} ms", r.duration_ms),
+        None => println!("  exit:     killed by a signal after {} ms", r.duration_ms),
+    }
+
+    println!("\ninput:");
+    print_indented(&serde_json::to_string_pretty(&r.input)?);
+    match &r.output {
+        Some(output) => {
+            println!("\noutput:");
+            print_indented(&serde_json::to_string_pretty(output)?);
+        }
+        None if !r.stdout.is_empty() => {
+            println!("\nstdout:");
+            print_indented(&r.stdout);
+        }
+        None => {}
+    }
+    if !r.stderr.trim().is_empty() {
+        println!("\nstderr:");
+        print_indented(&r.stderr);
+    }
+    if !r.problems.is_empty() {
+        println!("\nproblems:");
+        for problem in &r.problems {
+            println!("  - {problem}");
+        }
+    }
+    Ok(())
+}
+
+fn print_indented(text: &str) {
+    for line in text.trim_end().lines() {
+        println!("  {line}");
+    }
+}
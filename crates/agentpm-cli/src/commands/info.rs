@@ -0,0 +1,130 @@
+use crate::commands::OutputFormat;
+use crate::env::EnvSpec;
+use crate::manifest::{MANIFEST_FILE, Manifest, ManifestKind};
+use crate::permissions::{NETWORK_NOTE, Permissions};
+use crate::prelude::*;
+use crate::project::Project;
+use crate::runner::runtime::Runtime;
+use anyhow::bail;
+use serde::Serialize;
+use std::{collections::BTreeMap, path::PathBuf};
+
+#[derive(Args, Debug)]
+pub struct InfoArgs {
+    /// Installed tool to describe (defaults to the current project)
+    #[arg(value_name = "NAME")]
+    name: Option<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "pretty")]
+    format: OutputFormat,
+}
+
+#[derive(Serialize)]
+struct Info {
+    name: String,
+    version: String,
+    kind: &'static str,
+    description: String,
+    path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entrypoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    runtime: Option<Runtime>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<String>,
+    permissions: Permissions,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, EnvSpec>,
+}
+
+impl InfoArgs {
+    pub async fn run(self) -> Result<()> {
+        let project = Project::discover()?;
+        let (dir, manifest) = match &self.name {
+            None => (project.root.clone(), project.manifest.clone()),
+            Some(name) => {
+                let dir = project.tool_dir(name)?;
+                let path = dir.join(MANIFEST_FILE);
+                if !path.is_file() {
+                    bail!("`{name}` is not installed in this project. Try: `agentpm install`.");
+                }
+                (dir, Manifest::load(&path)?)
+            }
+        };
+
+        let info = Info {
+            kind: match manifest.kind {
+                ManifestKind::Agent => "agent",
+                ManifestKind::Tool => "tool",
+            },
+            name: manifest.name,
+            version: manifest.version,
+            description: manifest.description,
+            path: dir,
+            entrypoint: manifest.entrypoint,
+            runtime: manifest.runtime,
+            tools: manifest.tools.iter().map(ToString::to_string).collect(),
+            permissions: manifest.permissions,
+            env: manifest.env,
+        };
+
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&info)?),
+            OutputFormat::Pretty => print_pretty(&info),
+        }
+        Ok(())
+    }
+}
+
+fn print_pretty(info: &Info) {
+    println!("{} {} ({})", info.name, info.version, info.kind);
+    if !info.description.is_empty() {
+        println!("{}", info.description);
+    }
+    println!();
+    println!("  path:        {}", info.path.display());
+    if let Some(entrypoint) = &info.entrypoint {
+        match info.runtime {
+            Some(runtime) => println!("  entrypoint:  {entrypoint} ({runtime:?} runtime)"),
+            None => println!("  entrypoint:  {entrypoint}"),
+        }
+    }
+    if !info.tools.is_empty() {
+        println!("  tools:       {}", info.tools.join(", "));
+    }
+
+    println!();
+    if info.permissions.is_empty() {
+        println!("Permissions: none declared");
+    } else {
+        println!("Permissions:");
+        for line in info.permissions.lines() {
+            println!("  {line}");
+        }
+        if info.permissions.any_network() {
+            println!("  note: {NETWORK_NOTE}");
+        }
+    }
+
+    if !info.env.is_empty() {
+        println!();
+        println!("Environment:");
+        let width = info.env.keys().map(String::len).max().unwrap_or(0);
+        for (name, spec) in &info.env {
+            let mut flags = vec![if spec.required {
+                "required"
+            } else {
+                "optional"
+            }];
+            if spec.secret {
+                flags.push("secret");
+            }
+            print!("  {name:width$}  ({})", flags.join(", "));
+            match &spec.description {
+                Some(d) => println!("  {d}"),
+                None => println!(),
+            }
+        }
+    }
+}
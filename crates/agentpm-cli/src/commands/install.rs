@@ -0,0 +1,198 @@
+use crate::commands::lock::LockModeArgs;
+use crate::io::fs::{copy_dir, remove_dir_if_exists};
+use crate::io::prompt::confirm;
+use crate::lockfile::{LockChange, LockedTool, Lockfile};
+use crate::manifest::{MANIFEST_FILE, Manifest};
+use crate::permissions::NETWORK_NOTE;
+use crate::prelude::*;
+use crate::project::Project;
+use crate::store::Store;
+use anyhow::{anyhow, bail};
+use futures::{StreamExt, TryStreamExt, stream};
+
+#[derive(Args, Debug)]
+pub struct InstallArgs {
+    #[command(flatten)]
+    mode: LockModeArgs,
+
+    /// Maximum number of parallel downloads
+    #[arg(long, default_value_t = 8)]
+    jobs: usize,
+
+    /// Accept new permissions requested by updated tools without asking
+    #[arg(long, short = 'y')]
+    yes: bool,
+}
+
+impl InstallArgs {
+    pub async fn run(self, base_url: String) -> Result<()> {
+        let cfg = Config::load(base_url)?;
+        let project = Project::discover()?;
+        let client = AgentPmClient::new(cfg.base_url.clone())?;
+        let store = Store::new(&cfg);
+
+        let (lock, previous) = self.mode.resolve(&project, &client).await?;
+
+        // Download whatever the store doesn't have yet
+        let mut missing = Vec::new();
+        for tool in &lock.tools {
+            if !store.contains(&tool.digest)? {
+                missing.push(tool);
+            }
+        }
+        if !missing.is_empty() && self.mode.frozen {
+            let names: Vec<String> = missing
+                .iter()
+                .map(|t| format!("{}@{}", t.name, t.version))
+                .collect();
+            bail!(
+                "--frozen forbids downloading, but these tools are not cached: {}",
+                names.join(", ")
+            );
+        }
+        let downloaded = missing.len();
+        stream::iter(missing)
+            .map(|tool| fetch(&client, &store, tool))
+            .buffer_unordered(self.jobs.max(1))
+            .try_collect::<Vec<()>>()
+            .await?;
+
+        // Sync .agentpm/tools with the lockfile
+        let installed_path = project.installed_lock_path();
+        let installed = Lockfile::read(&installed_path)?;
+        let tools_dir = project.tools_dir();
+        let changes = lock.changes_from(installed.as_ref());
+
+        let grown = grown_permissions(&store, &project, &changes)?;
+        if !grown.is_empty() && !self.yes {
+            for (tool, lines) in &grown {
+                eprintln!("{tool} asks for new permissions:");
+                for line in lines {
+                    eprintln!("  + {line}");
+                }
+                if lines.iter().any(|l| l.starts_with("network:")) {
+                    eprintln!("  note: {NETWORK_NOTE}");
+                }
+            }
+            if !confirm("Install anyway?", "pass --yes to accept")? {
+                bail!(
+                    "Installation cancelled; {} and {} were left unchanged",
+                    project.lock_path().display(),
+                    tools_dir.display()
+                );
+            }
+        }
+        if previous.as_ref() != Some(&lock) {
+            lock.write(&project.lock_path())?;
+        }
+
+        for tool in &lock.tools {
+            let dest = project.tool_dir(&tool.name)?;
+            let changed = changes.iter().any(|c| match c {
+                LockChange::Added(t) | LockChange::Updated { to: t, .. } => t.name == tool.name,
+                LockChange::Removed(_) => false,
+            });
+            if changed || !dest.is_dir() {
+                remove_dir_if_exists(&dest)?;
+                copy_dir(&store.path(&tool.digest)?, &dest)?;
+            }
+        }
+        for change in &changes {
+            if let LockChange::Removed(t) = change {
+                remove_dir_if_exists(&project.tool_dir(&t.name)?)?;
+            }
+        }
+        lock.write(&installed_path)?;
+
+        // Summary
+        let (mut added, mut updated, mut removed) = (0, 0, 0);
+        for change in &changes {
+            match change {
+                LockChange::Added(_) => added += 1,
+                LockChange::Updated { .. } => updated += 1,
+                LockChange::Removed(_) => removed += 1,
+            }
+            println!("{change}");
+        }
+        println!(
+            "Installed {} tool(s) into {} ({added} added, {updated} updated, {removed} removed, {downloaded} downloaded)",
+            lock.tools.len(),
+            tools_dir.display()
+        );
+        Ok(())
+    }
+}
+
+/// Permissions that updated tools request beyond what their installed version declared,
+/// as (`name old -> new`, grants) pairs.
+fn grown_permissions(
+    store: &Store,
+    project: &Project,
+    changes: &[LockChange],
+) -> Result<Vec<(String, Vec<String>)>> {
+    let mut grown = Vec::new();
+    for change in changes {
+        let LockChange::Updated { from, to } = change else {
+            continue;
+        };
+        let installed = project.tool_dir(&to.name)?.join(MANIFEST_FILE);
+        let old_path = if installed.is_file() {
+            installed
+        } else {
+            store.path(&from.digest)?.join(MANIFEST_FILE)
+        };
+        if !old_path.is_file() {
+            // Nothing to compare against
+            continue;
+        }
+        let old = Manifest::load(&old_path)?;
+        let new = Manifest::load(&store.path(&to.digest)?.join(MANIFEST_FILE))?;
+        let lines = new.permissions.grown_from(&old.permissions);
+        if !lines.is_empty() {
+            grown.push((
+                format!("`{}` {} -> {}", to.name, from.version, to.version),
+                lines,
+            ));
+        }
+    }
+    Ok(grown)
+}
+
+/// Download one locked tool from its source registry and add it to the store.
+async fn fetch(client: &AgentPmClient, store: &Store, tool: &LockedTool) -> Result<()> {
+    let source_client;
+    let client = if tool.source == client.base_url() || tool.source.is_empty() {
+        client
+    } else {
+        source_client = AgentPmClient::new(tool.source.clone())?;
+        &source_client
+    };
+
+    let versions = client.tool_versions(&tool.name).await.with_context(|| {
+        format!(
+            "looking up {}@{} in {}",
+            tool.name, tool.version, tool.source
+        )
+    })?;
+    let published = versions
+        .iter()
+        .find(|v| v.version == tool.version)
+        .ok_or_else(|| {
+            anyhow!(
+                "{}@{} is no longer published by {}",
+                tool.name,
+                tool.version,
+                tool.source
+            )
+        })?;
+
+    debug!("downloading {} from {}", tool.name, published.archive_url);
+    let archive = client
+        .download(&published.archive_url)
+        .await
+        .with_context(|| format!("downloading {}@{}", tool.name, tool.version))?;
+    store
+        .add(&tool.digest, &archive)
+        .with_context(|| format!("installing {}@{}", tool.name, tool.version))?;
+    Ok(())
+}
@@ -1,10 +1,22 @@
+use crate::lint::{self, Catalog, FixMode, LintConfig, Linter, RULES, SchemaCache, Severity};
 use crate::prelude::*;
-use crate::util::{discover_manifest_files, load_json, load_schema_value};
+use crate::util::discover_manifest_files;
 use anyhow::anyhow;
-use jsonschema::{Draft, JSONSchema};
-use serde::Serialize;
-use serde_json::Value;
-use std::{fs, path::PathBuf};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default)]
+pub enum LintFormat {
+    #[default]
+    Pretty,
+    Json,
+    /// One JSON report per line
+    Ndjson,
+    /// SARIF 2.1.0, for code-scanning tools
+    Sarif,
+    /// JUnit XML, for CI test reports
+    Junit,
+    /// GitHub Actions annotations
+    Github,
+}
 
 #[derive(Args, Debug, Default)]
 pub struct LintArgs {
@@ -20,47 +32,60 @@ pub struct LintArgs {
     #[arg(long)]
     strict: bool,
 
-    /// Output format: pretty | json | ndjson
-    #[arg(long, default_value = "pretty")]
-    format: String,
+    /// Output format
+    #[arg(long, value_enum, default_value = "pretty")]
+    format: LintFormat,
 
     /// Attempt automatic fixes (non-invasive)
     #[arg(long)]
     fix: bool,
-}
 
-#[derive(Serialize)]
-struct LintIssue {
-    file: String,
-    level: &'static str, // "error" | "warning"
-    message: String,
-    instance_path: String,
-    schema_path: String,
-}
+    /// With --fix, print the changes as a unified diff instead of writing them
+    #[arg(long, requires = "fix")]
+    dry_run: bool,
+
+    /// Report RULE (ID or name) as an error; repeatable
+    #[arg(long, value_name = "RULE")]
+    deny: Vec<String>,
+
+    /// Report RULE as a warning; repeatable
+    #[arg(long, value_name = "RULE")]
+    warn: Vec<String>,
 
-#[derive(Serialize)]
-struct LintFileReport {
-    file: String,
-    ok: bool,
-    issues: Vec<LintIssue>,
+    /// Turn RULE off; repeatable
+    #[arg(long, value_name = "RULE")]
+    allow: Vec<String>,
+
+    /// Don't use the network: check agents' `tools` against the workspace and agentpm.lock
+    /// only, and use the cached (or built-in) manifest schema
+    #[arg(long)]
+    offline: bool,
+
+    /// List the available rules and exit
+    #[arg(long, conflicts_with_all = ["paths", "fix"])]
+    list_rules: bool,
 }
 
 impl LintArgs {
-    pub async fn run(self) -> Result<()> {
-        // Resolve schema
-        let schema_source = self.schema.unwrap_or_else(|| {
-            let local_path = PathBuf::from("schemas/agentpm.manifest.schema.json");
-            if local_path.exists() {
-                local_path.to_string_lossy().into_owned()
-            } else {
-                "https://raw.githubusercontent.com/agentpm-dev/cli/refs/heads/main/schemas/agentpm.manifest.schema.json".to_string()
-            }
-        });
-        let schema_value = load_schema_value(&schema_source)?;
-        let schema_static: &'static serde_json::Value = Box::leak(Box::new(schema_value));
-        let compiled = JSONSchema::options()
-            .with_draft(Draft::Draft202012)
-            .compile(schema_static)?;
+    pub async fn run(self, base_url: String) -> Result<()> {
+        if self.list_rules {
+            print_rules();
+            return Ok(());
+        }
+
+        let config = LintConfig::discover(&std::env::current_dir()?)?.with_flags(
+            &self.allow,
+            &self.warn,
+            &self.deny,
+        )?;
+        let fix = match (self.fix, self.dry_run) {
+            (false, _) => FixMode::Off,
+            (true, false) => FixMode::Write,
+            (true, true) => FixMode::DryRun,
+        };
+        let cfg = Config::load(base_url)?;
+        let schemas = SchemaCache::new(&cfg, self.offline);
+        let linter = Linter::new(self.schema.clone(), &schemas, config, self.strict, fix)?;
 
         // Discover manifest files
         let files = discover_manifest_files(&self.paths)?;
@@ -69,130 +94,49 @@ impl LintArgs {
             return Ok(());
         }
 
-        // Validate
-        let mut reports: Vec<LintFileReport> = Vec::new();
-
-        for file in files {
-            let (mut value, _) = match load_json(&file) {
-                Ok(v) => v,
-                Err(e) => {
-                    reports.push(LintFileReport {
-                        file: file.to_string_lossy().to_string(),
-                        ok: false,
-                        issues: vec![LintIssue {
-                            file: file.to_string_lossy().to_string(),
-                            level: "error",
-                            message: format!("Failed to parse JSON: {e}"),
-                            instance_path: "".into(),
-                            schema_path: "".into(),
-                        }],
-                    });
-                    continue;
-                }
-            };
-
-            let mut issues: Vec<LintIssue> = Vec::new();
-
-            // JSON Schema validation
-            if let Err(errors) = compiled.validate(&value) {
-                for e in errors {
-                    issues.push(LintIssue {
-                        file: file.to_string_lossy().to_string(),
-                        level: "error",
-                        message: e.to_string(),
-                        instance_path: e.instance_path.to_string(),
-                        schema_path: e.schema_path.to_string(),
-                    })
-                }
+        let mut catalog = Catalog::discover(&files);
+        if !self.offline {
+            let client = AgentPmClient::new(cfg.base_url)?;
+            catalog.fetch(&client, &files).await;
+            if catalog.registry_unreachable() {
+                eprintln!(
+                    "note: the registry could not be reached; `tools` were checked against local sources only"
+                );
             }
+        }
 
-            // Semantic warnings (examples)
-            // - recommend $schema present
-            if value.get("$schema").is_none() {
-                issues.push(LintIssue {
-                    file: file.to_string_lossy().to_string(),
-                    level: "warning",
-                    message: "Missing $schema; editors may lack IntelliSense.".into(),
-                    instance_path: "".into(),
-                    schema_path: "".into(),
-                });
-
-                // --fix: add $schema pointing at the same schema we used
-                if self.fix
-                    && let Some(obj) = value.as_object_mut()
-                {
-                    obj.insert("$schema".into(), Value::String(schema_source.clone()));
-                    // Write back
-                    let pretty = serde_json::to_string_pretty(&value)?;
-                    fs::write(&file, pretty + "\n").with_context(|| {
-                        format!("Failed to write fixed file {}", file.display())
-                    })?;
-                }
-            }
+        let reports = files
+            .iter()
+            .map(|file| linter.lint_file(file, &catalog))
+            .collect::<Result<Vec<_>>>()?;
 
-            // - warn if description is empty
-            if let Some(Value::String(desc)) = value.get("description")
-                && desc.trim().is_empty()
-            {
-                issues.push(LintIssue {
-                    file: file.to_string_lossy().to_string(),
-                    level: "warning",
-                    message: "`description` should not be empty".into(),
-                    instance_path: "/description".into(),
-                    schema_path: "".into(),
-                });
+        // Diffs go where they can't corrupt machine-readable output
+        for diff in reports.iter().filter_map(|r| r.diff.as_deref()) {
+            match self.format {
+                LintFormat::Pretty => print!("{diff}"),
+                _ => eprint!("{diff}"),
             }
-
-            let has_error = issues.iter().any(|i| i.level == "error");
-            let has_warning = issues.iter().any(|i| i.level == "warning");
-
-            let ok = if self.strict {
-                // strict: any warning or error fails
-                !has_error && !has_warning
-            } else {
-                // non-strict: errors fail, warnings allowed
-                !has_error
-            };
-            reports.push(LintFileReport {
-                file: file.to_string_lossy().to_string(),
-                ok,
-                issues,
-            });
         }
 
         // Output
-        match self.format.as_str() {
-            "json" => {
+        match self.format {
+            LintFormat::Json => {
                 println!("{}", serde_json::to_string_pretty(&reports)?);
             }
-            "ndjson" => {
+            LintFormat::Ndjson => {
                 for report in &reports {
                     println!("{}", serde_json::to_string(&report)?);
                 }
             }
-            _ => {
-                // pretty
-                for r in &reports {
-                    if r.ok {
-                        println!("✓ {}", r.file);
-                    } else {
-                        println!("✗ {}", r.file);
-                    }
-                    for i in &r.issues {
-                        let badge = match i.level {
-                            "error" => "ERROR",
-                            _ => "WARN ",
-                        };
-                        println!("  [{badge}] {}", i.message);
-                        if !i.instance_path.is_empty() {
-                            println!("        at instance {}", i.instance_path);
-                        }
-                        if !i.schema_path.is_empty() {
-                            println!("        vs schema  {}", i.schema_path);
-                        }
-                    }
-                }
+            LintFormat::Sarif => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&lint::sarif::render(&reports))?
+                );
             }
+            LintFormat::Junit => print!("{}", lint::junit::render(&reports, self.strict)),
+            LintFormat::Github => print!("{}", lint::github::render(&reports)),
+            LintFormat::Pretty => lint::print_pretty(&reports),
         }
 
         // Exit code
@@ -203,3 +147,18 @@ impl LintArgs {
         Ok(())
     }
 }
+
+fn print_rules() {
+    let name_width = RULES.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    for rule in RULES {
+        let level = match rule.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let fix = if rule.fixable { "fix" } else { "" };
+        println!(
+            "{}  {:<name_width$}  {:<7}  {:<3}  {}",
+            rule.id, rule.name, level, fix, rule.description
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use crate::io::fs::write_atomic;
+use crate::lockfile::Lockfile;
+use crate::manifest::Manifest;
+use crate::prelude::*;
+use crate::project::Project;
+use crate::resolve::{ResolveOptions, Resolver, Update};
+use anyhow::bail;
+use std::fs;
+
+#[derive(Args, Debug, Default)]
+pub struct LockArgs {
+    #[command(flatten)]
+    mode: LockModeArgs,
+}
+
+/// How agentpm.lock may be used and changed. Shared by every command that resolves tools.
+#[derive(Args, Debug, Default, Clone)]
+pub struct LockModeArgs {
+    /// Ignore agentpm.lock and resolve every tool to the newest matching version
+    #[arg(long, conflicts_with_all = ["locked", "frozen"])]
+    pub update: bool,
+
+    /// Fail if agentpm.lock is missing or out of date instead of rewriting it
+    #[arg(long)]
+    pub locked: bool,
+
+    /// Like --locked, and never contact the registry
+    #[arg(long)]
+    pub frozen: bool,
+}
+
+impl LockArgs {
+    pub async fn run(self, base_url: String) -> Result<()> {
+        let cfg = Config::load(base_url)?;
+        let project = Project::discover()?;
+        let client = AgentPmClient::new(cfg.base_url.clone())?;
+
+        let (lock, previous) = self.mode.resolve(&project, &client).await?;
+        if previous.as_ref() != Some(&lock) {
+            lock.write(&project.lock_path())?;
+            for change in lock.changes_from(previous.as_ref()) {
+                println!("{change}");
+            }
+            println!(
+                "Locked {} tool(s) in {}",
+                lock.tools.len(),
+                project.lock_path().display()
+            );
+        } else {
+            println!("{} is up to date", project.lock_path().display());
+        }
+        Ok(())
+    }
+}
+
+/// Re-resolve for the manifest edited to `text`, letting the tools in `update` move, then
+/// write agent.json and agentpm.lock together so a failed resolution leaves both untouched.
+/// Returns the new lockfile and the previous one.
+pub async fn relock(
+    project: &Project,
+    text: &str,
+    client: &AgentPmClient,
+    update: Update,
+) -> Result<(Lockfile, Option<Lockfile>)> {
+    let path = project.manifest_path();
+    let manifest: Manifest = serde_json::from_str(text)
+        .with_context(|| format!("Invalid manifest {}", path.display()))?;
+    let previous = Lockfile::read(&project.lock_path())?;
+    let opts = ResolveOptions {
+        update,
+        offline: false,
+    };
+    let lock = Resolver::new(client, previous.as_ref(), opts)
+        .resolve(&manifest)
+        .await?;
+
+    let current =
+        fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    if current != text {
+        write_atomic(&path, text)?;
+    }
+    if previous.as_ref() != Some(&lock) {
+        lock.write(&project.lock_path())?;
+    }
+    Ok((lock, previous))
+}
+
+impl LockModeArgs {
+    /// Resolve the project's tools per the lock mode. Returns the new lockfile and the one
+    /// on disk (if any); writing it is left to the caller, once nothing else can fail.
+    pub async fn resolve(
+        &self,
+        project: &Project,
+        client: &AgentPmClient,
+    ) -> Result<(Lockfile, Option<Lockfile>)> {
+        let lock_path = project.lock_path();
+        let previous = Lockfile::read(&lock_path)?;
+        let strict = self.locked || self.frozen;
+        if strict && previous.is_none() {
+            bail!(
+                "{} is missing and --locked/--frozen forbids creating it",
+                lock_path.display()
+            );
+        }
+
+        let opts = ResolveOptions {
+            update: if self.update {
+                Update::All
+            } else {
+                Update::Nothing
+            },
+            offline: self.frozen,
+        };
+        let lock = Resolver::new(client, previous.as_ref(), opts)
+            .resolve(&project.manifest)
+            .await?;
+
+        if previous.as_ref() == Some(&lock) {
+            return Ok((lock, previous));
+        }
+        if strict {
+            let changes: Vec<String> = lock
+                .changes_from(previous.as_ref())
+                .iter()
+                .map(|c| format!("  {c}"))
+                .collect();
+            bail!(
+                "{} needs to be updated but --locked/--frozen was passed:\n{}",
+                lock_path.display(),
+                changes.join("\n")
+            );
+        }
+        Ok((lock, previous))
+    }
+}
@@ -1,7 +1,21 @@
 use crate::prelude::*;
+pub mod add;
+pub mod dev;
+pub mod fuzz;
+pub mod history;
+pub mod info;
 pub mod init;
+pub mod install;
 pub mod lint;
+pub mod lock;
 pub mod login;
+pub mod outdated;
+pub mod remove;
+pub mod replay;
+pub mod run;
+pub mod test;
+pub mod tree;
+pub mod update;
 pub mod whoami;
 
 #[derive(Subcommand, Debug)]
@@ -17,4 +31,54 @@ pub enum Commands {
 
     /// Lint agent.json (tool or agent)
     Lint(lint::LintArgs),
+
+    /// Resolve tools and write agentpm.lock
+    Lock(lock::LockArgs),
+
+    /// Download the agent's tools into .agentpm/tools
+    Install(install::InstallArgs),
+
+    /// Add tools to the agent's `tools` and update agentpm.lock
+    Add(add::AddArgs),
+
+    /// Remove tools from the agent's `tools` and update agentpm.lock
+    Remove(remove::RemoveArgs),
+
+    /// List tools with newer versions available
+    Outdated(outdated::OutdatedArgs),
+
+    /// Move locked tools to the newest versions their ranges allow
+    Update(update::UpdateArgs),
+
+    /// Show the resolved dependency graph
+    Tree(tree::TreeArgs),
+
+    /// Show the project's or an installed tool's details and declared permissions
+    Info(info::InfoArgs),
+
+    /// Run the tool locally: JSON input on stdin, JSON output on stdout
+    Run(run::RunArgs),
+
+    /// Run the tool's golden test cases (tests/*.json and `examples`)
+    Test(test::TestArgs),
+
+    /// Run the tool on random inputs from its `inputs` schema and check every output
+    Fuzz(fuzz::FuzzArgs),
+
+    /// Watch the project and re-run lint and test cases on every change
+    Dev(dev::DevArgs),
+
+    /// List and inspect recorded `agentpm run` invocations
+    History(history::HistoryArgs),
+
+    /// Re-run a recorded run with the same input and diff the output against the recording
+    Replay(replay::ReplayArgs),
+}
+
+/// Output format for commands that support machine-readable output
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default)]
+pub enum OutputFormat {
+    #[default]
+    Pretty,
+    Json,
 }
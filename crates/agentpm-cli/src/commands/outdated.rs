@@ -0,0 +1,104 @@
+use crate::commands::OutputFormat;
+use crate::lockfile::Lockfile;
+use crate::prelude::*;
+use crate::project::Project;
+use crate::resolve::{ResolveOptions, Resolver};
+use anyhow::anyhow;
+use semver::VersionReq;
+use serde::Serialize;
+
+#[derive(Args, Debug)]
+pub struct OutdatedArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value = "pretty")]
+    format: OutputFormat,
+}
+
+#[derive(Serialize)]
+struct OutdatedTool {
+    name: String,
+    /// Range declared in agent.json
+    range: String,
+    /// Version pinned in agentpm.lock
+    current: Option<String>,
+    /// Newest version within `range`
+    wanted: Option<String>,
+    /// Newest version published
+    latest: Option<String>,
+}
+
+impl OutdatedArgs {
+    pub async fn run(self, base_url: String) -> Result<()> {
+        let cfg = Config::load(base_url)?;
+        let project = Project::discover()?;
+        let client = AgentPmClient::new(cfg.base_url.clone())?;
+        let lock = Lockfile::read(&project.lock_path())?.ok_or_else(|| {
+            anyhow!(
+                "{} not found. Try: `agentpm lock`.",
+                project.lock_path().display()
+            )
+        })?;
+
+        let mut resolver = Resolver::new(&client, None, ResolveOptions::default());
+        let mut rows = Vec::new();
+        for tool in &project.manifest.tools {
+            let name = tool.name();
+            let wanted = resolver.newest(name, &tool.version_req()?).await?;
+            let latest = resolver.newest(name, &VersionReq::STAR).await?;
+            let current = lock.get(name).map(|t| t.version.clone());
+
+            let wanted = wanted.map(|v| v.to_string());
+            let latest = latest.map(|v| v.to_string());
+            if current == wanted && current == latest {
+                continue;
+            }
+            rows.push(OutdatedTool {
+                name: name.to_string(),
+                range: tool.range().unwrap_or("*").to_string(),
+                current,
+                wanted,
+                latest,
+            });
+        }
+
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+            OutputFormat::Pretty => print_table(&rows),
+        }
+        Ok(())
+    }
+}
+
+fn print_table(rows: &[OutdatedTool]) {
+    if rows.is_empty() {
+        println!("All tools are up to date.");
+        return;
+    }
+
+    let cell = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".into());
+    let mut table = vec![["Tool", "Range", "Current", "Wanted", "Latest"].map(String::from)];
+    for r in rows {
+        table.push([
+            r.name.clone(),
+            r.range.clone(),
+            cell(&r.current),
+            cell(&r.wanted),
+            cell(&r.latest),
+        ]);
+    }
+
+    let mut widths = [0; 5];
+    for row in &table {
+        for (w, c) in widths.iter_mut().zip(row) {
+            *w = (*w).max(c.len());
+        }
+    }
+    for row in &table {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(c, w)| format!("{c:<w$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
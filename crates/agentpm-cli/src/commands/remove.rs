@@ -0,0 +1,51 @@
+use crate::commands::lock::relock;
+use crate::manifest::{self, ManifestKind};
+use crate::prelude::*;
+use crate::project::Project;
+use crate::resolve::Update;
+use anyhow::bail;
+use std::fs;
+
+#[derive(Args, Debug)]
+pub struct RemoveArgs {
+    /// Names of the tools to remove
+    #[arg(value_name = "NAME", required = true)]
+    tools: Vec<String>,
+}
+
+impl RemoveArgs {
+    pub async fn run(self, base_url: String) -> Result<()> {
+        let cfg = Config::load(base_url)?;
+        let project = Project::discover()?;
+        let client = AgentPmClient::new(cfg.base_url.clone())?;
+
+        if project.manifest.kind == ManifestKind::Tool {
+            bail!(
+                "{} is a `kind: tool` manifest; only agents can compose tools",
+                project.manifest_path().display()
+            );
+        }
+
+        let path = project.manifest_path();
+        let mut text =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        let mut done = Vec::new();
+
+        for name in &self.tools {
+            match manifest::remove_tool(&text, name)? {
+                Some(updated) => text = updated,
+                None => bail!("`{name}` is not in the `tools` of {}", path.display()),
+            }
+            done.push(format!("Removed {name}"));
+        }
+
+        let (lock, previous) = relock(&project, &text, &client, Update::Nothing).await?;
+        for line in done {
+            println!("{line}");
+        }
+        for change in lock.changes_from(previous.as_ref()) {
+            println!("{change}");
+        }
+        Ok(())
+    }
+}
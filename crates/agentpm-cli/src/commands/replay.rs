@@ -0,0 +1,118 @@
+use crate::commands::run::RunLimitArgs;
+use crate::history::{History, RunRecord, package_digest};
+use crate::prelude::*;
+use crate::project::Project;
+use crate::runner::ToolPackage;
+use anyhow::bail;
+use similar::TextDiff;
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// Run id, as listed by `agentpm history list`
+    id: u64,
+
+    #[command(flatten)]
+    limits: RunLimitArgs,
+}
+
+impl ReplayArgs {
+    pub async fn run(self, base_url: String) -> Result<()> {
+        let cfg = Config::load(base_url)?;
+        let history = History::new(&cfg);
+        let old = history.get(self.id)?;
+
+        let project = Project::at(old.project.clone()).with_context(|| {
+            format!(
+                "run #{} came from {}, which no longer holds a project",
+                old.id,
+                old.project.display()
+            )
+        })?;
+        if project.manifest.name != old.tool {
+            bail!(
+                "{} now holds `{}`, not `{}` as in run #{}",
+                old.project.display(),
+                project.manifest.name,
+                old.tool,
+                old.id
+            );
+        }
+        let tool = ToolPackage::new(project.root.clone(), project.manifest.clone())?;
+        if tool.manifest.version != old.version {
+            eprintln!(
+                "note: `{}` is {} now, run #{} used {}",
+                old.tool, tool.manifest.version, old.id, old.version
+            );
+        } else if package_digest(&tool)? != old.digest {
+            eprintln!(
+                "note: agent.json or the entrypoint changed since run #{}",
+                old.id
+            );
+        }
+
+        let opts = self.limits.options(&project)?;
+        let outcome = tool.run(&old.input, &opts)?;
+        let new = RunRecord::new(&tool, &old.input, &outcome)?;
+        let new_id = history.add(new.clone())?;
+
+        let before = shown(&old)?;
+        let after = shown(&new)?;
+        if before == after && old.problems == new.problems {
+            println!("✓ run #{new_id} matches run #{}", old.id);
+            return Ok(());
+        }
+
+        if before != after {
+            let diff = TextDiff::from_lines(&before, &after);
+            print!(
+                "{}",
+                diff.unified_diff()
+                    .context_radius(3)
+                    .header(&format!("run #{}", old.id), &format!("run #{new_id}"))
+            );
+        }
+        if old.problems != new.problems {
+            for problem in &old.problems {
+                println!("- problem: {problem}");
+            }
+            for problem in &new.problems {
+                println!("+ problem: {problem}");
+            }
+        }
+        bail!("run #{new_id} differs from run #{}", old.id);
+    }
+}
+
+/// What a run answered, as text to diff: pretty JSON output, else raw stdout.
+fn shown(record: &RunRecord) -> Result<String> {
+    Ok(match &record.output {
+        Some(output) => serde_json::to_string_pretty(output)? + "\n",
+        None => record.stdout.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diffs_json_output_pretty_and_anything_else_raw() {
+        let mut record: RunRecord = serde_json::from_value(json!({
+            "id": 1,
+            "timestamp": 0,
+            "tool": "echo",
+            "version": "1.0.0",
+            "digest": "sha256:00",
+            "project": "/work/echo",
+            "input": {},
+            "stdout": "plain text",
+            "exit_code": 0,
+            "duration_ms": 1,
+        }))
+        .unwrap();
+        assert_eq!(shown(&record).unwrap(), "plain text");
+        record.output = Some(json!({ "a": [1] }));
+        assert_eq!(shown(&record).unwrap(), "{\n  \"a\": [\n    1\n  ]\n}\n");
+    }
+}
@@ -0,0 +1,246 @@
+use crate::env::{DOTENV_FILE, Redactor};
+use crate::history::{History, RunRecord};
+use crate::manifest::ManifestKind;
+use crate::prelude::*;
+use crate::project::Project;
+use crate::runner::steps::{StepFile, StepTrace, run_steps};
+use crate::runner::{RunOptions, RunOutcome, ToolPackage, sandbox::Sandbox};
+use anyhow::{anyhow, bail};
+use serde_json::{Map, Value};
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// JSON file holding the tool input (`-` reads stdin)
+    #[arg(long, value_name = "FILE", conflicts_with = "args")]
+    input: Option<PathBuf>,
+
+    /// Set one input field; VALUE is parsed as JSON when it can be, else taken as a string
+    #[arg(long = "arg", value_name = "KEY=VALUE")]
+    args: Vec<String>,
+
+    /// Run an agent locally: call installed tools as scripted in this step file,
+    /// wiring outputs to inputs with `{"$ref": "/steps/<id>/output/..."}`. Each tool call
+    /// is recorded in the history
+    #[arg(long, value_name = "FILE")]
+    steps: Option<PathBuf>,
+
+    #[command(flatten)]
+    limits: RunLimitArgs,
+}
+
+/// Sandboxing and resource limits for tool runs.
+#[derive(Args, Debug, Clone)]
+pub struct RunLimitArgs {
+    /// Confine the tool (Linux only) to its declared `permissions`: read-only package dir,
+    /// a scratch dir as HOME/TMPDIR, CPU and memory limits. Network access is all or
+    /// nothing: declaring any host allows every host
+    #[arg(long)]
+    sandbox: bool,
+
+    /// CPU time limit in seconds (with --sandbox)
+    #[arg(long, value_name = "SECS", default_value_t = 30, requires = "sandbox")]
+    cpu_time: u64,
+
+    /// Memory limit in MiB (with --sandbox)
+    #[arg(long, value_name = "MIB", default_value_t = 512, requires = "sandbox")]
+    memory: u64,
+
+    /// Kill the tool after this many seconds [default: 60 with --sandbox, none otherwise]
+    #[arg(long, value_name = "SECS")]
+    timeout: Option<f64>,
+}
+
+impl RunLimitArgs {
+    /// Options for running `project`'s tool; declared variables may come from its `.env`.
+    pub fn options(&self, project: &Project) -> Result<RunOptions> {
+        let timeout = match self.timeout {
+            Some(secs) => Some(
+                Duration::try_from_secs_f64(secs)
+                    .map_err(|_| anyhow!("--timeout expects a positive number of seconds"))?,
+            ),
+            None if self.sandbox => Some(Duration::from_secs(60)),
+            None => None,
+        };
+        Ok(RunOptions {
+            sandbox: self.sandbox.then_some(Sandbox {
+                cpu_time: self.cpu_time,
+                memory: self.memory,
+                permissions: project.manifest.permissions.clone(),
+            }),
+            timeout,
+            dotenv: Some(project.root.join(DOTENV_FILE)),
+        })
+    }
+}
+
+impl RunArgs {
+    pub async fn run(self, base_url: String) -> Result<()> {
+        let project = Project::discover()?;
+        if let Some(path) = &self.steps {
+            return self.run_steps(&project, path, base_url);
+        }
+        if project.manifest.kind == ManifestKind::Agent {
+            bail!(
+                "`{}` is an agent; run it with a step file of tool calls: `agentpm run --steps <FILE>`",
+                project.manifest.name
+            );
+        }
+        let tool = ToolPackage::new(project.root.clone(), project.manifest.clone())?;
+        let input = self.input_value()?;
+
+        let opts = self.limits.options(&project)?;
+
+        let outcome = tool.run(&input, &opts)?;
+        match record(base_url, &tool, &input, &outcome) {
+            Ok(id) => debug!("recorded as run #{id}"),
+            Err(e) => eprintln!("warning: could not record this run in the history: {e:#}"),
+        }
+        if !outcome.stderr.is_empty() {
+            eprint!("{}", outcome.stderr);
+        }
+        match &outcome.output {
+            Some(output) => println!(
+                "{}",
+                outcome
+                    .secrets
+                    .redact(&serde_json::to_string_pretty(output)?)
+            ),
+            None => print!("{}", outcome.stdout),
+        }
+        debug!(
+            "{} exited with {} after {:?}",
+            tool.manifest.name, outcome.status, outcome.duration
+        );
+
+        if !outcome.ok() {
+            bail!(
+                "`{}` failed:\n  {}",
+                tool.manifest.name,
+                outcome.problems.join("\n  ")
+            );
+        }
+        Ok(())
+    }
+
+    fn run_steps(&self, project: &Project, path: &Path, base_url: String) -> Result<()> {
+        let file = StepFile::load(path)?;
+        let input = match (&file.input, self.input.is_none() && self.args.is_empty()) {
+            (Some(input), true) => input.clone(),
+            _ => self.input_value()?,
+        };
+        let opts = self.limits.options(project)?;
+
+        let history = Config::load(base_url).map(|cfg| History::new(&cfg));
+        if let Err(e) = &history {
+            eprintln!("warning: tool calls will not be recorded in the history: {e:#}");
+        }
+        let run = run_steps(
+            project,
+            &file,
+            input,
+            &opts,
+            history.as_ref().ok(),
+            print_step,
+        )?;
+        if let Some(output) = &run.output {
+            println!(
+                "{}",
+                run.secrets.redact(&serde_json::to_string_pretty(output)?)
+            );
+        }
+        if !run.ok() {
+            let mut problems = run.problems.clone();
+            if let Some(step) = run.trace.iter().find(|s| !s.problems.is_empty()) {
+                problems.extend(
+                    step.problems
+                        .iter()
+                        .map(|p| format!("step `{}` ({}): {p}", step.id, step.tool)),
+                );
+            }
+            bail!(
+                "`{}` failed:\n  {}",
+                project.manifest.name,
+                run.secrets.redact(&problems.join("\n  "))
+            );
+        }
+        Ok(())
+    }
+
+    fn input_value(&self) -> Result<Value> {
+        if let Some(path) = &self.input {
+            let text = if path.as_os_str() == "-" {
+                let mut s = String::new();
+                std::io::stdin().read_to_string(&mut s)?;
+                s
+            } else {
+                fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?
+            };
+            return serde_json::from_str(&text).context("input is not valid JSON");
+        }
+
+        let mut obj = Map::new();
+        for arg in &self.args {
+            let (key, raw) = arg
+                .split_once('=')
+                .ok_or_else(|| anyhow!("--arg expects KEY=VALUE, got `{arg}`"))?;
+            let value =
+                serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()));
+            obj.insert(key.to_string(), value);
+        }
+        Ok(Value::Object(obj))
+    }
+}
+
+/// Save the run to the history for `agentpm history` and `agentpm replay`.
+fn record(
+    base_url: String,
+    tool: &ToolPackage,
+    input: &Value,
+    outcome: &RunOutcome,
+) -> Result<u64> {
+    let cfg = Config::load(base_url)?;
+    History::new(&cfg).add(RunRecord::new(tool, input, outcome)?)
+}
+
+/// One trace entry on stderr: the call, then its input and output on a line each. Steps
+/// pass secrets between them, so everything shown goes through `secrets`.
+fn print_step(step: &StepTrace, secrets: &Redactor) {
+    let mark = if step.problems.is_empty() {
+        "✓"
+    } else {
+        "✗"
+    };
+    eprintln!(
+        "{mark} {} → {}@{} ({} ms)",
+        step.id,
+        step.tool,
+        step.version,
+        step.duration.as_millis()
+    );
+    eprintln!("    in:  {}", abbreviate(&step.input, secrets));
+    if let Some(output) = &step.output {
+        eprintln!("    out: {}", abbreviate(output, secrets));
+    }
+    if !step.problems.is_empty() {
+        for line in secrets.redact(step.stderr.trim_end()).lines() {
+            eprintln!("    | {line}");
+        }
+    }
+}
+
+/// Compact JSON, redacted and then cut to one terminal line.
+fn abbreviate(value: &Value, secrets: &Redactor) -> String {
+    const MAX: usize = 100;
+    let text = secrets.redact(&value.to_string());
+    if text.chars().count() <= MAX {
+        return text;
+    }
+    let cut: String = text.chars().take(MAX).collect();
+    format!("{cut}…")
+}
@@ -0,0 +1,119 @@
+use crate::commands::run::RunLimitArgs;
+use crate::prelude::*;
+use crate::project::Project;
+use crate::suite::{SuiteReport, run_suite};
+use crate::util::junit;
+use anyhow::bail;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default)]
+pub enum TestFormat {
+    #[default]
+    Pretty,
+    Json,
+    /// JUnit XML, for CI test reports
+    Junit,
+}
+
+#[derive(Args, Debug)]
+pub struct TestArgs {
+    /// Only run cases whose name contains FILTER
+    #[arg(value_name = "FILTER")]
+    filter: Option<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "pretty")]
+    format: TestFormat,
+
+    #[command(flatten)]
+    limits: RunLimitArgs,
+}
+
+impl TestArgs {
+    pub async fn run(self) -> Result<()> {
+        let project = Project::discover()?;
+        let opts = self.limits.options(&project)?;
+        let report = run_suite(&project, self.filter.as_deref(), &opts)?;
+
+        match self.format {
+            TestFormat::Pretty => print_pretty(&report),
+            TestFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            TestFormat::Junit => print!("{}", junit::render(&[to_junit(&report)])),
+        }
+
+        if report.failed > 0 {
+            bail!(
+                "{} of {} test case(s) failed",
+                report.failed,
+                report.cases.len()
+            );
+        }
+        Ok(())
+    }
+}
+
+pub fn print_pretty(report: &SuiteReport) {
+    if report.cases.is_empty() {
+        println!(
+            "No test cases for `{}` (add tests/*.json or `examples` to agent.json)",
+            report.tool
+        );
+        return;
+    }
+    println!(
+        "running {} case(s) for `{}`",
+        report.cases.len(),
+        report.tool
+    );
+    for case in &report.cases {
+        let mark = if case.ok { "✓" } else { "✗" };
+        println!("  {mark} {} ({} ms)", case.name, case.duration.as_millis());
+        for failure in &case.failures {
+            for (i, line) in failure.lines().enumerate() {
+                let lead = if i == 0 { "-" } else { " " };
+                println!("      {lead} {line}");
+            }
+        }
+        if !case.ok && !case.stderr.trim().is_empty() {
+            println!("      stderr:");
+            for line in case.stderr.trim_end().lines() {
+                println!("        {line}");
+            }
+        }
+    }
+    println!(
+        "test result: {}. {} passed; {} failed; finished in {:.2}s",
+        if report.failed == 0 { "ok" } else { "FAILED" },
+        report.passed,
+        report.failed,
+        report.duration.as_secs_f64()
+    );
+}
+
+fn to_junit(report: &SuiteReport) -> junit::Suite {
+    junit::Suite {
+        name: report.tool.clone(),
+        cases: report
+            .cases
+            .iter()
+            .map(|case| junit::Case {
+                name: case.name.clone(),
+                classname: format!("{}.{}", report.tool, case.source),
+                time: case.duration,
+                failures: (!case.ok)
+                    .then(|| {
+                        (
+                            case.failures
+                                .first()
+                                .and_then(|f| f.lines().next())
+                                .unwrap_or_default()
+                                .to_string(),
+                            case.failures.join("\n"),
+                        )
+                    })
+                    .into_iter()
+                    .collect(),
+                stderr: case.stderr.clone(),
+            })
+            .collect(),
+    }
+}
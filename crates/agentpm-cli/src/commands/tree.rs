@@ -0,0 +1,239 @@
+use crate::lockfile::Lockfile;
+use crate::prelude::*;
+use crate::project::Project;
+use anyhow::{anyhow, bail};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+#[derive(Args, Debug)]
+pub struct TreeArgs {
+    /// Maximum depth to display (0 shows only the root)
+    #[arg(long)]
+    depth: Option<usize>,
+
+    /// Show what depends on NAME instead of what the agent depends on
+    #[arg(long, short = 'i', value_name = "NAME")]
+    invert: Option<String>,
+
+    /// Only show tools required by more than one package, and by whom
+    #[arg(long, short = 'd', conflicts_with = "invert")]
+    duplicates: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "pretty")]
+    format: TreeFormat,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum TreeFormat {
+    Pretty,
+    /// Graphviz; render with `dot -Tsvg`
+    Dot,
+}
+
+/// Dependency graph keyed by tool name (resolution is flat, so names are unique).
+struct Graph {
+    labels: BTreeMap<String, String>,
+    edges: BTreeMap<String, Vec<String>>,
+}
+
+impl TreeArgs {
+    pub async fn run(self) -> Result<()> {
+        let project = Project::discover()?;
+        let lock = Lockfile::read(&project.lock_path())?.ok_or_else(|| {
+            anyhow!(
+                "{} not found. Try: `agentpm lock`.",
+                project.lock_path().display()
+            )
+        })?;
+        let graph = Graph::build(&project, &lock)?;
+        let root = project.manifest.name.clone();
+
+        if self.duplicates {
+            let inverted = graph.inverted();
+            let dups: Vec<&String> = inverted
+                .edges
+                .iter()
+                .filter(|(_, dependents)| dependents.len() > 1)
+                .map(|(name, _)| name)
+                .collect();
+            match self.format {
+                TreeFormat::Pretty => {
+                    if dups.is_empty() {
+                        println!("No tool is required by more than one package.");
+                    }
+                    for (i, name) in dups.into_iter().enumerate() {
+                        if i > 0 {
+                            println!();
+                        }
+                        inverted.print(name, Some(1));
+                    }
+                }
+                TreeFormat::Dot => inverted.print_dot(&dups, Some(1)),
+            }
+            return Ok(());
+        }
+
+        let (graph, root) = match &self.invert {
+            Some(name) => {
+                if !graph.labels.contains_key(name) {
+                    bail!("`{name}` is not part of the dependency graph");
+                }
+                (graph.inverted(), name.clone())
+            }
+            None => (graph, root),
+        };
+
+        match self.format {
+            TreeFormat::Pretty => graph.print(&root, self.depth),
+            TreeFormat::Dot => graph.print_dot(&[&root], self.depth),
+        }
+        Ok(())
+    }
+}
+
+impl Graph {
+    fn build(project: &Project, lock: &Lockfile) -> Result<Self> {
+        let manifest = &project.manifest;
+        let mut labels = BTreeMap::new();
+        let mut edges: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        labels.insert(
+            manifest.name.clone(),
+            format!("{} v{}", manifest.name, manifest.version),
+        );
+        let mut roots = Vec::new();
+        for tool in &manifest.tools {
+            if !roots.iter().any(|r| r == tool.name()) {
+                roots.push(tool.name().to_string());
+            }
+        }
+        edges.insert(manifest.name.clone(), roots.clone());
+
+        for tool in &lock.tools {
+            labels.insert(
+                tool.name.clone(),
+                format!("{} v{}", tool.name, tool.version),
+            );
+            let mut deps = Vec::new();
+            for (dep, _) in tool.requirements()? {
+                if !deps.contains(&dep) {
+                    deps.push(dep);
+                }
+            }
+            edges.insert(tool.name.clone(), deps);
+        }
+
+        // Anything referenced but not locked
+        let referenced: BTreeSet<String> = edges.values().flatten().cloned().collect();
+        for name in referenced {
+            labels
+                .entry(name.clone())
+                .or_insert_with(|| format!("{name} (not locked)"));
+        }
+
+        Ok(Self { labels, edges })
+    }
+
+    /// Same nodes, edges pointing from a tool to the packages that require it.
+    fn inverted(&self) -> Self {
+        let mut edges: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (from, tos) in &self.edges {
+            for to in tos {
+                edges.entry(to.clone()).or_default().push(from.clone());
+            }
+        }
+        Self {
+            labels: self.labels.clone(),
+            edges,
+        }
+    }
+
+    fn children(&self, node: &str) -> &[String] {
+        self.edges.get(node).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn label<'a>(&'a self, node: &'a str) -> &'a str {
+        self.labels.get(node).map(String::as_str).unwrap_or(node)
+    }
+
+    fn print(&self, root: &str, depth: Option<usize>) {
+        println!("{}", self.label(root));
+        let mut seen = HashSet::from([root.to_string()]);
+        let mut path = vec![root.to_string()];
+        self.print_children(root, "", depth, &mut path, &mut seen);
+    }
+
+    fn print_children(
+        &self,
+        node: &str,
+        prefix: &str,
+        depth: Option<usize>,
+        path: &mut Vec<String>,
+        seen: &mut HashSet<String>,
+    ) {
+        if depth == Some(path.len() - 1) {
+            return;
+        }
+        let children = self.children(node);
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let connector = if last { "└── " } else { "├── " };
+            let mut line = format!("{prefix}{connector}{}", self.label(child));
+
+            let expand = if path.contains(child) {
+                line.push_str(" (cycle)");
+                false
+            } else if !seen.insert(child.clone()) && !self.children(child).is_empty() {
+                // Already expanded elsewhere
+                line.push_str(" (*)");
+                false
+            } else {
+                true
+            };
+            println!("{line}");
+
+            if expand {
+                path.push(child.clone());
+                let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+                self.print_children(child, &prefix, depth, path, seen);
+                path.pop();
+            }
+        }
+    }
+
+    /// One graph drawn from every node in `roots`.
+    fn print_dot(&self, roots: &[&String], depth: Option<usize>) {
+        // Breadth-first so --depth means the same thing as in the pretty tree
+        let mut levels: BTreeMap<String, usize> =
+            roots.iter().map(|root| (root.to_string(), 0)).collect();
+        let mut queue: VecDeque<String> = roots.iter().map(|root| root.to_string()).collect();
+        let mut edges = Vec::new();
+        while let Some(node) = queue.pop_front() {
+            let level = levels[&node];
+            if depth.is_some_and(|d| level >= d) {
+                continue;
+            }
+            for child in self.children(&node) {
+                edges.push((node.clone(), child.clone()));
+                if !levels.contains_key(child) {
+                    levels.insert(child.clone(), level + 1);
+                    queue.push_back(child.clone());
+                }
+            }
+        }
+
+        println!("digraph agentpm {{");
+        println!("  rankdir=LR;");
+        for node in levels.keys() {
+            println!("  {} [label={}];", dot_id(node), dot_id(self.label(node)));
+        }
+        for (from, to) in edges {
+            println!("  {} -> {};", dot_id(&from), dot_id(&to));
+        }
+        println!("}}");
+    }
+}
+
+fn dot_id(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
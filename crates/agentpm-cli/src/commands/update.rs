@@ -0,0 +1,136 @@
+use crate::commands::OutputFormat;
+use crate::commands::lock::relock;
+use crate::lockfile::{LockChange, Lockfile};
+use crate::manifest;
+use crate::prelude::*;
+use crate::project::Project;
+use crate::resolve::{ResolveOptions, Resolver, Update};
+use anyhow::bail;
+use semver::VersionReq;
+use serde::Serialize;
+use std::fs;
+
+#[derive(Args, Debug)]
+pub struct UpdateArgs {
+    /// Tools to update (defaults to all of them)
+    #[arg(value_name = "NAME")]
+    tools: Vec<String>,
+
+    /// Also raise ranges in agent.json so the newest published versions are allowed
+    #[arg(long)]
+    latest: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "pretty")]
+    format: OutputFormat,
+}
+
+#[derive(Serialize, Default)]
+struct UpdateReport {
+    /// Ranges rewritten in agent.json (--latest)
+    manifest: Vec<Change>,
+    /// Versions that moved in agentpm.lock
+    lock: Vec<Change>,
+}
+
+#[derive(Serialize)]
+struct Change {
+    name: String,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+impl UpdateArgs {
+    pub async fn run(self, base_url: String) -> Result<()> {
+        let cfg = Config::load(base_url)?;
+        let project = Project::discover()?;
+        let client = AgentPmClient::new(cfg.base_url.clone())?;
+        let previous = Lockfile::read(&project.lock_path())?;
+
+        for name in &self.tools {
+            let direct = project.manifest.tools.iter().any(|t| t.name() == name);
+            let locked = previous.as_ref().is_some_and(|l| l.get(name).is_some());
+            if !direct && !locked {
+                bail!("`{name}` is neither in `tools` nor in agentpm.lock");
+            }
+        }
+        let in_scope = |name: &str| self.tools.is_empty() || self.tools.iter().any(|n| n == name);
+
+        let mut report = UpdateReport::default();
+        let mut resolver = Resolver::new(&client, None, ResolveOptions::default());
+
+        let path = project.manifest_path();
+        let mut text =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        // --latest: widen ranges that exclude the newest version
+        if self.latest {
+            for tool in &project.manifest.tools {
+                if !in_scope(tool.name()) {
+                    continue;
+                }
+                let Some(latest) = resolver.newest(tool.name(), &VersionReq::STAR).await? else {
+                    continue;
+                };
+                if tool.version_req()?.matches(&latest) {
+                    continue;
+                }
+                let range = format!("^{latest}");
+                text = manifest::set_tool(&text, tool.name(), &range)?;
+                report.manifest.push(Change {
+                    name: tool.name().to_string(),
+                    from: tool.range().map(String::from),
+                    to: Some(range),
+                });
+            }
+        }
+
+        // agent.json is only rewritten once the new ranges resolve
+        let update = if self.tools.is_empty() {
+            Update::All
+        } else {
+            Update::Only(self.tools.clone())
+        };
+        let (lock, previous) = relock(&project, &text, &client, update).await?;
+        for change in lock.changes_from(previous.as_ref()) {
+            report.lock.push(match change {
+                LockChange::Added(t) => Change {
+                    name: t.name.clone(),
+                    from: None,
+                    to: Some(t.version.clone()),
+                },
+                LockChange::Updated { from, to } => Change {
+                    name: to.name.clone(),
+                    from: Some(from.version.clone()),
+                    to: Some(to.version.clone()),
+                },
+                LockChange::Removed(t) => Change {
+                    name: t.name.clone(),
+                    from: Some(t.version.clone()),
+                    to: None,
+                },
+            });
+        }
+
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            OutputFormat::Pretty => {
+                for c in &report.manifest {
+                    println!(
+                        "Raised {} from {} to {} in agent.json",
+                        c.name,
+                        c.from.as_deref().unwrap_or("*"),
+                        c.to.as_deref().unwrap_or("*")
+                    );
+                }
+                for change in lock.changes_from(previous.as_ref()) {
+                    println!("{change}");
+                }
+                if report.lock.is_empty() {
+                    println!("agentpm.lock is already up to date");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -8,9 +8,10 @@ use std::path::PathBuf;
 #[derive(Debug, Clone)]
 pub struct Config {
     pub base_url: String,
-    #[allow(dead_code)] // TODO: will be used when we add config file writes
     pub config_dir: PathBuf,
     pub token_file: PathBuf,
+    /// Downloaded tool packages, shared by every project
+    pub cache_dir: PathBuf,
 }
 
 /// What can come from config.toml (all optional).
@@ -28,6 +29,7 @@ impl Config {
         let config_dir = dirs.config_dir().to_path_buf();
         let cfg_path = config_dir.join("config.toml");
         let token_file = config_dir.join("token.json");
+        let cache_dir = dirs.cache_dir().to_path_buf();
 
         // Read the file if it exists
         let file_cfg: Option<FileConfig> = if cfg_path.exists() {
@@ -56,6 +58,7 @@ impl Config {
             base_url,
             config_dir,
             token_file,
+            cache_dir,
         })
     }
 }
@@ -0,0 +1,320 @@
+use crate::prelude::*;
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::Path};
+
+pub const DOTENV_FILE: &str = ".env";
+
+/// Secret values shorter than this are not redacted; masking every "1" or "on" in the
+/// output would hide more than it protects.
+const MIN_SECRET_LEN: usize = 4;
+
+/// One entry of the `env` section of agent.json.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default = "required_default")]
+    pub required: bool,
+    /// Never print the value
+    #[serde(default)]
+    pub secret: bool,
+}
+
+fn required_default() -> bool {
+    true
+}
+
+/// The declared variables a tool gets, and which of their values must not be printed.
+#[derive(Debug, Clone, Default)]
+pub struct ToolEnv {
+    pub vars: BTreeMap<String, String>,
+    pub secrets: Redactor,
+}
+
+impl ToolEnv {
+    /// Look up every declared variable in the process environment, then in `dotenv`.
+    /// Fails listing every required variable that is set in neither.
+    pub fn resolve(
+        tool: &str,
+        specs: &BTreeMap<String, EnvSpec>,
+        dotenv: Option<&Path>,
+    ) -> Result<Self> {
+        let file = match dotenv {
+            Some(path) => load_dotenv(path)?,
+            None => BTreeMap::new(),
+        };
+
+        let mut env = ToolEnv::default();
+        let mut missing = Vec::new();
+        for (name, spec) in specs {
+            let value = std::env::var(name)
+                .ok()
+                .or_else(|| file.get(name).cloned())
+                .filter(|v| !v.is_empty());
+            match value {
+                Some(value) => {
+                    if spec.secret {
+                        env.secrets.add(&value);
+                    }
+                    env.vars.insert(name.clone(), value);
+                }
+                None if spec.required => missing.push(match &spec.description {
+                    Some(d) => format!("{name}  {d}"),
+                    None => name.clone(),
+                }),
+                None => {}
+            }
+        }
+
+        if !missing.is_empty() {
+            let hint = match dotenv {
+                Some(path) => format!("Set them in the environment or in {}", path.display()),
+                None => "Set them in the environment".to_string(),
+            };
+            bail!(
+                "`{tool}` needs environment variables that are not set:\n  {}\n{hint}",
+                missing.join("\n  ")
+            );
+        }
+        Ok(env)
+    }
+
+    /// Keep only the variables `permissions.env` allows, for sandboxed runs; returns the
+    /// names that were withheld.
+    pub fn retain_permitted(&mut self, permitted: &[String]) -> Vec<String> {
+        let mut withheld = Vec::new();
+        self.vars.retain(|name, _| {
+            let keep = permitted.contains(name);
+            if !keep {
+                withheld.push(name.clone());
+            }
+            keep
+        });
+        withheld
+    }
+}
+
+/// Replaces secret values in text shown to the user.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    secrets: Vec<String>,
+}
+
+impl Redactor {
+    /// Mask `secret`, also as it appears inside JSON strings and `{:?}` output.
+    pub fn add(&mut self, secret: &str) {
+        if secret.len() < MIN_SECRET_LEN {
+            return;
+        }
+        let json = serde_json::to_string(secret).unwrap_or_default();
+        let debug = format!("{secret:?}");
+        for form in [secret, unquote(&json), unquote(&debug)] {
+            if !form.is_empty() && !self.secrets.iter().any(|s| s == form) {
+                self.secrets.push(form.to_string());
+            }
+        }
+        // Longest first, so a secret containing another is masked whole
+        self.secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    }
+
+    /// Also mask everything `other` masks.
+    pub fn merge(&mut self, other: &Redactor) {
+        for secret in &other.secrets {
+            if !self.secrets.contains(secret) {
+                self.secrets.push(secret.clone());
+            }
+        }
+        self.secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for secret in &self.secrets {
+            if out.contains(secret.as_str()) {
+                out = out.replace(secret.as_str(), "[redacted]");
+            }
+        }
+        out
+    }
+
+    /// Mask secrets inside a JSON value, keeping it valid JSON: strings and keys are
+    /// redacted in place, and a number that spells out a secret becomes `"[redacted]"`.
+    pub fn redact_value(&self, value: &Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.redact(s)),
+            Value::Number(n) if self.redact(&n.to_string()) != n.to_string() => {
+                Value::String("[redacted]".into())
+            }
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|v| self.redact_value(v)).collect())
+            }
+            Value::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|(k, v)| (self.redact(k), self.redact_value(v)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+fn unquote(quoted: &str) -> &str {
+    quoted
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(quoted)
+}
+
+/// Parse a `.env` file: `KEY=value` lines, optional `export `, `#` comments,
+/// single quotes taken literally, double quotes with `\n`-style escapes.
+/// A missing file is empty.
+pub fn load_dotenv(path: &Path) -> Result<BTreeMap<String, String>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+    };
+
+    let mut vars = BTreeMap::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, raw)) = line.split_once('=') else {
+            bail!("{}:{}: expected KEY=VALUE", path.display(), index + 1);
+        };
+        let value =
+            parse_value(raw.trim()).with_context(|| format!("{}:{}", path.display(), index + 1))?;
+        vars.insert(key.trim().to_string(), value);
+    }
+    Ok(vars)
+}
+
+fn parse_value(raw: &str) -> Result<String> {
+    if let Some(rest) = raw.strip_prefix('\'') {
+        let Some(end) = rest.find('\'') else {
+            bail!("unterminated single quote");
+        };
+        return Ok(rest[..end].to_string());
+    }
+    if let Some(rest) = raw.strip_prefix('"') {
+        let mut out = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Ok(out),
+                '\\' => match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('r') => out.push('\r'),
+                    Some('t') => out.push('\t'),
+                    Some(other) => out.push(other),
+                    None => break,
+                },
+                c => out.push(c),
+            }
+        }
+        bail!("unterminated double quote");
+    }
+    // Unquoted: a ` #` starts a comment
+    let value = raw.split_once(" #").map_or(raw, |(v, _)| v);
+    Ok(value.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dotenv(text: &str) -> Result<BTreeMap<String, String>> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DOTENV_FILE);
+        fs::write(&path, text).unwrap();
+        load_dotenv(&path)
+    }
+
+    #[test]
+    fn parses_dotenv_lines() {
+        let vars = dotenv(
+            "# comment\n\
+             \n\
+             PLAIN=value # trailing comment\n\
+             export EXPORTED = spaced\n\
+             SINGLE='kept $literally \\n # too'\n\
+             DOUBLE=\"line\\nnext\\t\\\"quoted\\\" # kept\"\n\
+             HASH=a#b\n\
+             EMPTY=\n\
+             EQUALS=a=b\n",
+        )
+        .unwrap();
+        let expected = [
+            ("PLAIN", "value"),
+            ("EXPORTED", "spaced"),
+            ("SINGLE", "kept $literally \\n # too"),
+            ("DOUBLE", "line\nnext\t\"quoted\" # kept"),
+            ("HASH", "a#b"),
+            ("EMPTY", ""),
+            ("EQUALS", "a=b"),
+        ];
+        assert_eq!(
+            vars,
+            expected
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_dotenv_lines() {
+        let err = dotenv("OK=1\nnot a pair\n").unwrap_err();
+        assert!(
+            format!("{err:#}").contains(":2: expected KEY=VALUE"),
+            "{err:#}"
+        );
+        let err = dotenv("A='open\n").unwrap_err();
+        assert!(
+            format!("{err:#}").contains("unterminated single quote"),
+            "{err:#}"
+        );
+        let err = dotenv("A=\"open\n").unwrap_err();
+        assert!(
+            format!("{err:#}").contains("unterminated double quote"),
+            "{err:#}"
+        );
+
+        let missing = tempfile::tempdir().unwrap();
+        assert!(
+            load_dotenv(&missing.path().join(DOTENV_FILE))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn redacts_secrets_in_every_form() {
+        let mut secrets = Redactor::default();
+        secrets.add("abc");
+        secrets.add("pa\"ss\nword");
+        assert_eq!(secrets.redact("abc"), "abc", "too short to mask");
+        assert_eq!(secrets.redact("raw: pa\"ss\nword"), "raw: [redacted]");
+        assert_eq!(
+            secrets.redact(r#"{"token":"pa\"ss\nword"}"#),
+            r#"{"token":"[redacted]"}"#
+        );
+
+        // A secret containing another is masked whole, whichever was added first
+        let mut inner = Redactor::default();
+        inner.add("token");
+        let mut outer = Redactor::default();
+        outer.add("token-and-more");
+        inner.merge(&outer);
+        assert_eq!(
+            inner.redact("x token-and-more token"),
+            "x [redacted] [redacted]"
+        );
+    }
+}
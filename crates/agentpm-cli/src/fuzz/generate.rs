@@ -0,0 +1,428 @@
+use super::Rng;
+use serde_json::{Map, Number, Value, json};
+
+/// Past this nesting depth only the smallest valid values are produced.
+const MAX_DEPTH: usize = 6;
+
+/// Strings that tend to break parsers and string handling.
+const NASTY_STRINGS: &[&str] = &[
+    "",
+    " ",
+    "null",
+    "0",
+    "\"quoted\"",
+    "back\\slash",
+    "line\nbreak",
+    "tab\there",
+    "héllo wörld",
+    "日本語テキスト",
+    "emoji 🎉🔥",
+    "<script>alert(1)</script>",
+    "'; DROP TABLE users; --",
+    "\u{0}nul",
+];
+
+/// A random value shaped by `schema`. `root` is the document `$ref`s point into.
+/// Keywords the generator doesn't understand (e.g. `pattern`) are left to the caller,
+/// who validates the result and retries.
+pub fn generate(schema: &Value, root: &Value, rng: &mut Rng, depth: usize) -> Value {
+    let obj = match schema {
+        Value::Object(obj) => obj,
+        Value::Bool(false) => return Value::Null,
+        _ => return any_scalar(rng),
+    };
+
+    if let Some(target) = obj
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| resolve(root, r))
+    {
+        return generate(target, root, rng, depth);
+    }
+    if let Some(value) = obj.get("const") {
+        return value.clone();
+    }
+    if let Some(Value::Array(options)) = obj.get("enum")
+        && !options.is_empty()
+    {
+        return rng.pick(options).clone();
+    }
+    if let Some(Value::Array(all)) = obj.get("allOf") {
+        let mut merged = without(obj, "allOf");
+        for part in all {
+            merge(&mut merged, part);
+        }
+        return generate(&Value::Object(merged), root, rng, depth);
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(Value::Array(branches)) = obj.get(key)
+            && !branches.is_empty()
+        {
+            let mut merged = without(obj, key);
+            merge(&mut merged, rng.pick(branches));
+            return generate(&Value::Object(merged), root, rng, depth);
+        }
+    }
+
+    match pick_type(obj, rng).as_str() {
+        "object" => object(obj, root, rng, depth),
+        "array" => array(obj, root, rng, depth),
+        "string" => string(obj, rng),
+        "integer" => integer(obj, rng),
+        "number" => number(obj, rng),
+        "boolean" => Value::Bool(rng.chance(0.5)),
+        "null" => Value::Null,
+        _ => any_scalar(rng),
+    }
+}
+
+fn pick_type(obj: &Map<String, Value>, rng: &mut Rng) -> String {
+    match obj.get("type") {
+        Some(Value::String(t)) => t.clone(),
+        Some(Value::Array(types)) if !types.is_empty() => {
+            rng.pick(types).as_str().unwrap_or("null").to_string()
+        }
+        _ => {
+            let has = |keys: &[&str]| keys.iter().any(|k| obj.contains_key(*k));
+            if has(&["properties", "required", "additionalProperties"]) {
+                "object".into()
+            } else if has(&["items", "prefixItems", "minItems", "maxItems"]) {
+                "array".into()
+            } else if has(&["minLength", "maxLength", "pattern", "format"]) {
+                "string".into()
+            } else if has(&["minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum"]) {
+                "number".into()
+            } else {
+                String::new()
+            }
+        }
+    }
+}
+
+fn object(obj: &Map<String, Value>, root: &Value, rng: &mut Rng, depth: usize) -> Value {
+    let required: Vec<&str> = obj
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let empty = Map::new();
+    let properties = obj
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+
+    let mut out = Map::new();
+    for (key, schema) in properties {
+        if required.contains(&key.as_str()) || (depth < MAX_DEPTH && rng.chance(0.5)) {
+            out.insert(key.clone(), generate(schema, root, rng, depth + 1));
+        }
+    }
+    // Required members without a schema of their own
+    for key in required {
+        if !out.contains_key(key) {
+            out.insert(key.to_string(), any_scalar(rng));
+        }
+    }
+    match obj.get("additionalProperties") {
+        Some(Value::Bool(false)) => {}
+        extra if depth < MAX_DEPTH && rng.chance(0.2) => {
+            let schema = match extra {
+                Some(s @ Value::Object(_)) => s.clone(),
+                _ => json!({}),
+            };
+            for i in 0..rng.below(3) {
+                out.insert(
+                    format!("extra_{i}"),
+                    generate(&schema, root, rng, depth + 1),
+                );
+            }
+        }
+        _ => {}
+    }
+    Value::Object(out)
+}
+
+fn array(obj: &Map<String, Value>, root: &Value, rng: &mut Rng, depth: usize) -> Value {
+    let min = uint(obj, "minItems").unwrap_or(0);
+    let max = uint(obj, "maxItems").unwrap_or(min + 5).max(min);
+    let len = if depth >= MAX_DEPTH {
+        min
+    } else {
+        min + rng.below(max.min(min + 5) - min + 1)
+    };
+
+    let prefix = obj
+        .get("prefixItems")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let items = obj.get("items").cloned().unwrap_or(json!({}));
+    let unique = obj.get("uniqueItems") == Some(&Value::Bool(true));
+
+    let mut out: Vec<Value> = Vec::new();
+    let mut attempts = 0;
+    while (out.len() as u64) < len && attempts < len * 10 {
+        attempts += 1;
+        let schema = prefix.get(out.len()).unwrap_or(&items);
+        let value = generate(schema, root, rng, depth + 1);
+        if unique && out.contains(&value) {
+            continue;
+        }
+        out.push(value);
+    }
+    Value::Array(out)
+}
+
+fn string(obj: &Map<String, Value>, rng: &mut Rng) -> Value {
+    if let Some(format) = obj.get("format").and_then(Value::as_str)
+        && let Some(s) = formatted(format, rng)
+    {
+        return Value::String(s);
+    }
+
+    let min = uint(obj, "minLength").unwrap_or(0);
+    let max = uint(obj, "maxLength").unwrap_or(min + 32).max(min);
+    let fits = |s: &str| (min..=max).contains(&(s.chars().count() as u64));
+
+    if rng.chance(0.3) {
+        let nasty: Vec<&&str> = NASTY_STRINGS.iter().filter(|s| fits(s)).collect();
+        if !nasty.is_empty() {
+            return Value::String(rng.pick(&nasty).to_string());
+        }
+    }
+    let len = if rng.chance(0.1) {
+        max.min(min + 256)
+    } else {
+        min + rng.below(max.min(min + 32) - min + 1)
+    };
+    const CHARS: &[char] = &[
+        'a', 'b', 'c', 'x', 'y', 'z', 'A', 'Q', '0', '7', ' ', '-', '_', '.', ',', 'é', 'ß', '中',
+    ];
+    Value::String((0..len).map(|_| *rng.pick(CHARS)).collect())
+}
+
+fn formatted(format: &str, rng: &mut Rng) -> Option<String> {
+    let n = rng.below(10_000);
+    Some(match format {
+        "email" => format!("user{n}@example.com"),
+        "uri" | "url" => format!("https://example.com/item/{n}?q={}", rng.below(100)),
+        "uuid" => format!(
+            "{:08x}-{:04x}-4{:03x}-a{:03x}-{:012x}",
+            rng.next_u64() as u32,
+            rng.below(0x10000),
+            rng.below(0x1000),
+            rng.below(0x1000),
+            rng.next_u64() & 0xffff_ffff_ffff
+        ),
+        "date" => format!(
+            "20{:02}-{:02}-{:02}",
+            rng.below(40),
+            1 + rng.below(12),
+            1 + rng.below(28)
+        ),
+        "date-time" => format!(
+            "20{:02}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            rng.below(40),
+            1 + rng.below(12),
+            1 + rng.below(28),
+            rng.below(24),
+            rng.below(60),
+            rng.below(60)
+        ),
+        "ipv4" => format!(
+            "{}.{}.{}.{}",
+            rng.below(256),
+            rng.below(256),
+            rng.below(256),
+            rng.below(256)
+        ),
+        _ => return None,
+    })
+}
+
+fn integer(obj: &Map<String, Value>, rng: &mut Rng) -> Value {
+    let (lo, hi) = bounds(obj, 1.0);
+    let (lo, hi) = (lo.ceil() as i64, hi.floor() as i64);
+    if lo > hi {
+        return json!(lo);
+    }
+    let step = obj
+        .get("multipleOf")
+        .and_then(Value::as_f64)
+        .filter(|m| *m >= 1.0 && m.fract() == 0.0)
+        .map(|m| m as i64);
+
+    let edges: Vec<i64> = [lo, hi, 0, 1, -1]
+        .into_iter()
+        .filter(|e| (lo..=hi).contains(e))
+        .collect();
+    let value = if rng.chance(0.3) {
+        *rng.pick(&edges)
+    } else {
+        rng.range_i64(lo, hi)
+    };
+    let Some(step) = step else {
+        return json!(value);
+    };
+    // The next multiple up, or the one below it past `hi`; in i128 so the ends of i64 don't overflow
+    let (value, step) = (i128::from(value), i128::from(step));
+    let up = value + (-value).rem_euclid(step);
+    let value = if up > i128::from(hi) { up - step } else { up };
+    json!(value.clamp(i128::from(lo), i128::from(hi)) as i64)
+}
+
+fn number(obj: &Map<String, Value>, rng: &mut Rng) -> Value {
+    let (lo, hi) = bounds(obj, 1e-6);
+    if lo > hi {
+        return json!(lo);
+    }
+    let edges = [lo, hi, 0.0, 0.5, -0.5];
+    let value = if rng.chance(0.3) {
+        *rng.pick(&edges)
+    } else {
+        lo + rng.f64() * (hi - lo)
+    };
+    let value = value.clamp(lo, hi);
+    Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+/// Inclusive numeric bounds; `epsilon` nudges exclusive bounds inward.
+fn bounds(obj: &Map<String, Value>, epsilon: f64) -> (f64, f64) {
+    let get = |key: &str| obj.get(key).and_then(Value::as_f64);
+    let lo = get("minimum")
+        .or(get("exclusiveMinimum").map(|v| v + epsilon))
+        .unwrap_or(-1000.0);
+    let hi = get("maximum")
+        .or(get("exclusiveMaximum").map(|v| v - epsilon))
+        .unwrap_or(lo.max(0.0) + 2000.0);
+    (lo, hi)
+}
+
+fn any_scalar(rng: &mut Rng) -> Value {
+    match rng.below(4) {
+        0 => Value::Null,
+        1 => Value::Bool(rng.chance(0.5)),
+        2 => json!(rng.range_i64(-100, 100)),
+        _ => Value::String(rng.pick(NASTY_STRINGS).to_string()),
+    }
+}
+
+fn uint(obj: &Map<String, Value>, key: &str) -> Option<u64> {
+    obj.get(key).and_then(Value::as_u64)
+}
+
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    root.pointer(reference.strip_prefix('#')?)
+}
+
+fn without(obj: &Map<String, Value>, key: &str) -> Map<String, Value> {
+    let mut copy = obj.clone();
+    copy.remove(key);
+    copy
+}
+
+/// Fold `part` into `into`: `properties` and `required` are combined, other keywords overwrite.
+fn merge(into: &mut Map<String, Value>, part: &Value) {
+    let Some(part) = part.as_object() else {
+        return;
+    };
+    for (key, value) in part {
+        match (key.as_str(), into.get_mut(key), value) {
+            ("properties", Some(Value::Object(have)), Value::Object(more)) => {
+                have.extend(more.clone());
+            }
+            ("required", Some(Value::Array(have)), Value::Array(more)) => {
+                have.extend(
+                    more.iter()
+                        .filter(|v| !have.contains(v))
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                );
+            }
+            _ => {
+                into.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonschema::{Draft, JSONSchema};
+
+    fn samples(schema: &Value, seed: u64) -> Vec<Value> {
+        let mut rng = Rng::new(seed);
+        (0..200)
+            .map(|_| generate(schema, schema, &mut rng, 0))
+            .collect()
+    }
+
+    #[test]
+    fn generated_values_satisfy_the_schema() {
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "tags", "kind"],
+            "additionalProperties": false,
+            "properties": {
+                "id": { "type": "integer", "minimum": 1, "maximum": 100, "multipleOf": 5 },
+                "ratio": { "type": "number", "exclusiveMinimum": 0, "exclusiveMaximum": 1 },
+                "name": { "type": "string", "minLength": 2, "maxLength": 4 },
+                "email": { "type": "string", "format": "email" },
+                "tags": {
+                    "type": "array",
+                    "minItems": 1,
+                    "maxItems": 3,
+                    "uniqueItems": true,
+                    "items": { "enum": ["a", "b", "c"] },
+                },
+                "kind": { "$ref": "#/$defs/kind" },
+                "shape": {
+                    "oneOf": [
+                        { "type": "object", "required": ["r"], "properties": { "r": { "type": "number" } } },
+                        { "type": "null" },
+                    ],
+                },
+                "extent": {
+                    "allOf": [
+                        { "type": "object", "required": ["w"], "properties": { "w": { "const": 1 } } },
+                        { "required": ["h"], "properties": { "h": { "type": "boolean" } } },
+                    ],
+                },
+            },
+            "$defs": { "kind": { "type": "string", "enum": ["tool", "agent"] } },
+        });
+        let validator = JSONSchema::options()
+            .with_draft(Draft::Draft202012)
+            .compile(&schema)
+            .unwrap();
+        for value in samples(&schema, 7) {
+            if let Err(errors) = validator.validate(&value) {
+                let errors: Vec<String> = errors.map(|e| e.to_string()).collect();
+                panic!("{value} is invalid: {errors:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn integers_reach_the_ends_of_i64() {
+        let schema = json!({ "type": "integer", "minimum": i64::MIN, "maximum": i64::MAX });
+        let values = samples(&schema, 1);
+        assert!(values.iter().all(|v| v.is_i64()), "{values:?}");
+        assert!(values.contains(&json!(i64::MIN)));
+        assert!(values.contains(&json!(i64::MAX)));
+
+        let schema = json!({ "type": "integer", "minimum": i64::MIN, "multipleOf": 5 });
+        for value in samples(&schema, 1) {
+            assert_eq!(value.as_i64().unwrap() % 5, 0, "{value}");
+        }
+    }
+
+    #[test]
+    fn the_same_seed_generates_the_same_values() {
+        let schema =
+            json!({ "type": "array", "items": { "type": ["string", "number", "boolean"] } });
+        assert_eq!(samples(&schema, 42), samples(&schema, 42));
+        assert_ne!(samples(&schema, 42), samples(&schema, 43));
+    }
+}
@@ -0,0 +1,197 @@
+//! Contract testing: run a tool against random inputs drawn from its `inputs` schema and
+//! check it never crashes and always answers with output matching `outputs`.
+//!
+//! Inputs come from a seeded generator, so a run is reproduced by passing the same seed.
+//! The first failing input is shrunk (members dropped, strings shortened, numbers moved
+//! toward zero) for as long as the smaller input stays valid and still fails.
+
+mod generate;
+mod shrink;
+
+use crate::prelude::*;
+use crate::runner::{RunOptions, ToolPackage};
+use crate::util::serde_duration::as_millis;
+use anyhow::bail;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Generated inputs are validated against `inputs`; give up on one after this many tries.
+const GENERATE_ATTEMPTS: usize = 50;
+
+#[derive(Debug, Clone)]
+pub struct FuzzOptions {
+    /// Number of inputs to try
+    pub runs: usize,
+    pub seed: u64,
+    /// Tool runs to spend shrinking a failing input
+    pub max_shrinks: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FuzzReport {
+    pub tool: String,
+    pub seed: u64,
+    /// Inputs run, including the failing one
+    pub runs: usize,
+    #[serde(serialize_with = "as_millis", rename = "duration_ms")]
+    pub duration: Duration,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure: Option<Failure>,
+}
+
+/// The first input the tool failed on, and the smallest failing input found from it.
+#[derive(Debug, Serialize)]
+pub struct Failure {
+    /// 1-based index of the failing run
+    pub run: usize,
+    pub original: Value,
+    pub input: Value,
+    pub shrink_steps: usize,
+    /// Why `input` failed
+    pub problems: Vec<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+}
+
+/// Run `tool` on up to `opts.runs` generated inputs, stopping at the first failure.
+/// `on_run` is told whether each run passed.
+pub fn fuzz(
+    tool: &ToolPackage,
+    opts: &FuzzOptions,
+    run_opts: &RunOptions,
+    mut on_run: impl FnMut(bool),
+) -> Result<FuzzReport> {
+    let schema = tool.manifest.inputs.clone().unwrap_or_default();
+    let mut rng = Rng::new(opts.seed);
+    let started = Instant::now();
+    let mut report = FuzzReport {
+        tool: tool.manifest.name.clone(),
+        seed: opts.seed,
+        runs: 0,
+        duration: Duration::ZERO,
+        failure: None,
+    };
+
+    for run in 1..=opts.runs {
+        let Some(input) = generate_valid(tool, &schema, &mut rng) else {
+            bail!(
+                "could not generate an input matching the `inputs` schema of `{}` in {GENERATE_ATTEMPTS} tries \
+                 (keywords like `pattern` are not generated; consider `enum` or `format`)",
+                tool.manifest.name
+            );
+        };
+        report.runs = run;
+        let failed = problems(tool, &input, run_opts)?;
+        on_run(failed.is_none());
+        if let Some((problems, stderr)) = failed {
+            report.failure = Some(shrink(tool, input, problems, stderr, run, opts, run_opts)?);
+            break;
+        }
+    }
+    report.duration = started.elapsed();
+    Ok(report)
+}
+
+fn generate_valid(tool: &ToolPackage, schema: &Value, rng: &mut Rng) -> Option<Value> {
+    (0..GENERATE_ATTEMPTS)
+        .map(|_| generate::generate(schema, schema, rng, 0))
+        .find(|input| tool.check_input(input).is_empty())
+}
+
+/// Why `input` makes the tool fail, with its stderr; `None` if it passed.
+fn problems(
+    tool: &ToolPackage,
+    input: &Value,
+    opts: &RunOptions,
+) -> Result<Option<(Vec<String>, String)>> {
+    let outcome = tool.run(input, opts)?;
+    Ok((!outcome.ok()).then_some((outcome.problems, outcome.stderr)))
+}
+
+/// Greedily replace the failing input by the first simpler candidate that is still valid
+/// and still fails, until none does or the budget is spent.
+fn shrink(
+    tool: &ToolPackage,
+    original: Value,
+    problems: Vec<String>,
+    stderr: String,
+    run: usize,
+    opts: &FuzzOptions,
+    run_opts: &RunOptions,
+) -> Result<Failure> {
+    let mut failure = Failure {
+        run,
+        input: original.clone(),
+        original,
+        shrink_steps: 0,
+        problems,
+        stderr,
+    };
+    let mut budget = opts.max_shrinks;
+    'outer: while budget > 0 {
+        for candidate in shrink::candidates(&failure.input) {
+            if !tool.check_input(&candidate).is_empty() {
+                continue;
+            }
+            if budget == 0 {
+                break 'outer;
+            }
+            budget -= 1;
+            if let Some((problems, stderr)) = self::problems(tool, &candidate, run_opts)? {
+                debug!("shrunk to {candidate}");
+                failure.input = candidate;
+                failure.problems = problems;
+                failure.stderr = stderr;
+                failure.shrink_steps += 1;
+                continue 'outer;
+            }
+        }
+        break;
+    }
+    Ok(failure)
+}
+
+/// SplitMix64: tiny, seedable, and stable across releases so seeds stay reproducible.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n` (0 when `n` is 0).
+    pub fn below(&mut self, n: u64) -> u64 {
+        if n == 0 { 0 } else { self.next_u64() % n }
+    }
+
+    /// Uniform in `lo..=hi`.
+    pub fn range_i64(&mut self, lo: i64, hi: i64) -> i64 {
+        let span = hi.wrapping_sub(lo) as u64;
+        match span.checked_add(1) {
+            Some(n) => lo.wrapping_add(self.below(n) as i64),
+            None => self.next_u64() as i64,
+        }
+    }
+
+    /// Uniform in `0.0..1.0`.
+    pub fn f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.f64() < p
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
@@ -0,0 +1,162 @@
+use serde_json::{Value, json};
+
+/// Values one step simpler than `value`, most aggressive first: whole members and items
+/// are dropped before their contents are simplified. Candidates may violate the schema;
+/// the caller filters them.
+pub fn candidates(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Null | Value::Bool(false) => Vec::new(),
+        Value::Bool(true) => vec![Value::Bool(false)],
+        Value::Number(n) => numbers(n),
+        Value::String(s) => strings(s).into_iter().map(Value::String).collect(),
+        Value::Array(items) => {
+            let mut out = Vec::new();
+            if items.len() > 1 {
+                out.push(Value::Array(items[..items.len() / 2].to_vec()));
+            }
+            for i in 0..items.len() {
+                let mut fewer = items.clone();
+                fewer.remove(i);
+                out.push(Value::Array(fewer));
+            }
+            for (i, item) in items.iter().enumerate() {
+                for simpler in candidates(item) {
+                    let mut copy = items.clone();
+                    copy[i] = simpler;
+                    out.push(Value::Array(copy));
+                }
+            }
+            out
+        }
+        Value::Object(members) => {
+            let mut out = Vec::new();
+            for key in members.keys() {
+                let mut fewer = members.clone();
+                fewer.remove(key);
+                out.push(Value::Object(fewer));
+            }
+            for (key, member) in members {
+                for simpler in candidates(member) {
+                    let mut copy = members.clone();
+                    copy.insert(key.clone(), simpler);
+                    out.push(Value::Object(copy));
+                }
+            }
+            out
+        }
+    }
+}
+
+fn numbers(n: &serde_json::Number) -> Vec<Value> {
+    let mut out = Vec::new();
+    if let Some(i) = n.as_i64() {
+        if i != 0 {
+            out.push(json!(0));
+            // `i64::MIN` has no absolute value, but halving it is fine
+            if i.checked_abs().is_none_or(|a| a > 1) {
+                out.push(json!(i / 2));
+            }
+            if i < 0
+                && let Some(positive) = i.checked_neg()
+            {
+                out.push(json!(positive));
+            }
+        }
+    } else if let Some(f) = n.as_f64()
+        && f != 0.0
+    {
+        out.push(json!(0));
+        if f.fract() != 0.0 {
+            out.push(json!(f.trunc()));
+        }
+        if f.abs() > 1.0 {
+            out.push(json!(f / 2.0));
+        }
+        if f < 0.0 {
+            out.push(json!(-f));
+        }
+    }
+    out
+}
+
+fn strings(s: &str) -> Vec<String> {
+    if s.is_empty() {
+        return Vec::new();
+    }
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = vec![String::new()];
+    if chars.len() > 1 {
+        out.push(chars[..chars.len() / 2].iter().collect());
+        out.push(chars[chars.len() / 2..].iter().collect());
+        out.push(chars[..chars.len() - 1].iter().collect());
+    }
+    if !s.is_ascii() {
+        out.push(
+            chars
+                .iter()
+                .map(|c| if c.is_ascii() { *c } else { 'a' })
+                .collect(),
+        );
+    }
+    if chars.iter().any(|c| *c != 'a') {
+        out.push("a".repeat(chars.len()));
+    }
+    out.dedup();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The greedy loop `fuzz` runs, with `fails` standing in for the tool.
+    fn minimize(mut value: Value, fails: impl Fn(&Value) -> bool) -> Value {
+        while let Some(simpler) = candidates(&value).into_iter().find(|c| fails(c)) {
+            value = simpler;
+        }
+        value
+    }
+
+    #[test]
+    fn numbers_shrink_toward_zero_without_overflowing() {
+        assert_eq!(
+            candidates(&json!(i64::MIN)),
+            [json!(0), json!(i64::MIN / 2)]
+        );
+        assert_eq!(candidates(&json!(-4)), [json!(0), json!(-2), json!(4)]);
+        assert_eq!(candidates(&json!(1)), [json!(0)]);
+        assert_eq!(
+            candidates(&json!(u64::MAX)),
+            [json!(0), json!(u64::MAX as f64 / 2.0)]
+        );
+        assert_eq!(
+            candidates(&json!(-2.5)),
+            [json!(0), json!(-2.0), json!(-1.25), json!(2.5)]
+        );
+        assert!(candidates(&json!(0)).is_empty());
+    }
+
+    #[test]
+    fn containers_drop_parts_before_simplifying_them() {
+        let array = candidates(&json!([1, 2, 3]));
+        assert_eq!(
+            &array[..4],
+            [json!([1]), json!([2, 3]), json!([1, 3]), json!([1, 2])]
+        );
+        let object = candidates(&json!({ "a": true, "b": "x" }));
+        assert_eq!(&object[..2], [json!({ "b": "x" }), json!({ "a": true })]);
+        assert_eq!(candidates(&json!("héllo"))[0], json!(""));
+        assert!(candidates(&json!("héllo")).contains(&json!("hallo")));
+    }
+
+    #[test]
+    fn greedy_shrinking_finds_a_minimal_input() {
+        let input = json!({ "items": [3, 1, 4], "text": "abxcd", "n": i64::MIN });
+        let shrunk = minimize(input.clone(), |v| v["n"].as_i64().is_some_and(|n| n < 0));
+        assert_eq!(shrunk, json!({ "n": -1 }));
+        let shrunk = minimize(input, |v| {
+            v["text"].as_str().is_some_and(|s| s.contains('x'))
+        });
+        assert_eq!(shrunk, json!({ "text": "x" }));
+    }
+}
@@ -0,0 +1,338 @@
+use crate::env::Redactor;
+use crate::io::fs::write_atomic;
+use crate::prelude::*;
+use crate::runner::{RunOutcome, ToolPackage};
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{self, OpenOptions},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Oldest records are pruned past this many.
+const MAX_RECORDS: usize = 1000;
+
+/// One recorded `agentpm run`. Output, stdout and stderr are stored redacted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: u64,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub tool: String,
+    pub version: String,
+    /// Hash of agent.json and the entrypoint, to tell edited code from the recorded one
+    pub digest: String,
+    /// Project directory the tool ran from
+    pub project: PathBuf,
+    pub input: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    /// Raw stdout when it wasn't JSON
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stdout: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+    /// `None` when the tool was killed by a signal
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+}
+
+impl RunRecord {
+    pub fn new(tool: &ToolPackage, input: &Value, outcome: &RunOutcome) -> Result<Self> {
+        // `output` is the only unredacted field of an outcome; never write secrets to disk
+        let output = outcome
+            .output
+            .as_ref()
+            .map(|output| outcome.secrets.redact_value(output));
+        Ok(Self {
+            id: 0,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            tool: tool.manifest.name.clone(),
+            version: tool.manifest.version.clone(),
+            digest: package_digest(tool)?,
+            project: tool.dir.clone(),
+            input: input.clone(),
+            stdout: if output.is_none() {
+                outcome.stdout.clone()
+            } else {
+                String::new()
+            },
+            output,
+            stderr: outcome.stderr.clone(),
+            exit_code: outcome.status.code(),
+            duration_ms: outcome.duration.as_millis() as u64,
+            problems: outcome.problems.clone(),
+        })
+    }
+
+    /// Mask `secrets` everywhere in the record, input included.
+    pub fn redact(&mut self, secrets: &Redactor) {
+        self.input = secrets.redact_value(&self.input);
+        if let Some(output) = &self.output {
+            self.output = Some(secrets.redact_value(output));
+        }
+        self.stdout = secrets.redact(&self.stdout);
+        self.stderr = secrets.redact(&self.stderr);
+        for problem in &mut self.problems {
+            *problem = secrets.redact(problem);
+        }
+    }
+
+    /// "3m ago"-style age of the record.
+    pub fn age(&self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let secs = now.saturating_sub(self.timestamp);
+        match secs {
+            0..60 => format!("{secs}s ago"),
+            60..3600 => format!("{}m ago", secs / 60),
+            3600..86400 => format!("{}h ago", secs / 3600),
+            _ => format!("{}d ago", secs / 86400),
+        }
+    }
+}
+
+/// Hash of the files that decide a tool's behaviour: agent.json and the entrypoint.
+pub fn package_digest(tool: &ToolPackage) -> Result<String> {
+    let mut hasher = Sha256::new();
+    for path in [
+        tool.dir.join(crate::manifest::MANIFEST_FILE),
+        tool.entrypoint()?,
+    ] {
+        let bytes = fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
+    }
+    Ok(format!("sha256:{}", hex::encode(hasher.finalize())))
+}
+
+/// Recorded runs, one JSON file per run: `<config_dir>/history/<id>.json`.
+pub struct History {
+    root: PathBuf,
+}
+
+impl History {
+    pub fn new(cfg: &Config) -> Self {
+        Self {
+            root: cfg.config_dir.join("history"),
+        }
+    }
+
+    /// Store `record` under the next id and return that id.
+    pub fn add(&self, mut record: RunRecord) -> Result<u64> {
+        fs::create_dir_all(&self.root)
+            .with_context(|| format!("creating {}", self.root.display()))?;
+        let ids = self.ids()?;
+        // Claim the id with an empty file first, so concurrent runs never share one
+        record.id = ids.last().map_or(1, |last| last + 1);
+        loop {
+            let path = self.path(record.id);
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => break,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => record.id += 1,
+                Err(e) => return Err(e).with_context(|| format!("creating {}", path.display())),
+            }
+        }
+        write_atomic(
+            &self.path(record.id),
+            &(serde_json::to_string_pretty(&record)? + "\n"),
+        )?;
+
+        let excess = (ids.len() + 1).saturating_sub(MAX_RECORDS);
+        for id in &ids[..excess] {
+            let _ = fs::remove_file(self.path(*id));
+        }
+        Ok(record.id)
+    }
+
+    pub fn get(&self, id: u64) -> Result<RunRecord> {
+        let path = self.path(id);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                bail!("No recorded run #{id}. See `agentpm history list`.")
+            }
+            Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+        };
+        serde_json::from_str(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Every record, newest first. Unreadable files are skipped.
+    pub fn list(&self) -> Result<Vec<RunRecord>> {
+        let mut ids = self.ids()?;
+        ids.reverse();
+        Ok(ids.into_iter().filter_map(|id| self.get(id).ok()).collect())
+    }
+
+    /// Stored ids, ascending.
+    fn ids(&self) -> Result<Vec<u64>> {
+        if !self.root.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut ids: Vec<u64> = fs::read_dir(&self.root)
+            .with_context(|| format!("reading {}", self.root.display()))?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| id_of(&e.path()))
+            .collect();
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    fn path(&self, id: u64) -> PathBuf {
+        self.root.join(format!("{id}.json"))
+    }
+}
+
+fn id_of(path: &Path) -> Option<u64> {
+    if path.extension()? != "json" {
+        return None;
+    }
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(input: Value, output: Option<Value>) -> RunRecord {
+        RunRecord {
+            id: 0,
+            timestamp: 0,
+            tool: "echo".into(),
+            version: "1.0.0".into(),
+            digest: "sha256:00".into(),
+            project: PathBuf::from("/work/echo"),
+            input,
+            output,
+            stdout: String::new(),
+            stderr: "listening on 8080\n".into(),
+            exit_code: Some(0),
+            duration_ms: 5,
+            problems: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn redacts_secrets_that_are_not_json_strings() {
+        let mut secrets = Redactor::default();
+        secrets.add("8080");
+        secrets.add("s3cr\"et");
+
+        let mut run = record(
+            json!({ "port": 8080, "url": "http://localhost:8080/", "8080": true, "ok": 80801 }),
+            Some(json!(["s3cr\"et", 8080.5, 42])),
+        );
+        run.redact(&secrets);
+
+        assert_eq!(
+            run.input,
+            json!({
+                "port": "[redacted]",
+                "url": "http://localhost:[redacted]/",
+                "[redacted]": true,
+                "ok": "[redacted]",
+            })
+        );
+        assert_eq!(run.output, Some(json!(["[redacted]", "[redacted]", 42])));
+        assert_eq!(run.stderr, "listening on [redacted]\n");
+        let stored = serde_json::to_string(&run).unwrap();
+        assert!(
+            !stored.contains("8080") && !stored.contains("s3cr"),
+            "{stored}"
+        );
+    }
+
+    #[test]
+    fn stores_records_under_increasing_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History {
+            root: dir.path().join("history"),
+        };
+        assert!(history.list().unwrap().is_empty());
+        let err = history.get(1).unwrap_err();
+        assert!(err.to_string().contains("No recorded run #1"), "{err}");
+
+        let first = history.add(record(json!({ "n": 1 }), None)).unwrap();
+        let second = history.add(record(json!({ "n": 2 }), None)).unwrap();
+        assert_eq!((first, second), (1, 2));
+        assert_eq!(history.get(2).unwrap().input, json!({ "n": 2 }));
+
+        // Newest first; stray and broken files are left out
+        fs::write(history.root.join("notes.txt"), "").unwrap();
+        fs::write(history.root.join("3.json"), "{ broken").unwrap();
+        let ids: Vec<u64> = history.list().unwrap().iter().map(|r| r.id).collect();
+        assert_eq!(ids, [2, 1]);
+        assert_eq!(history.add(record(json!({}), None)).unwrap(), 4);
+    }
+
+    #[test]
+    fn prunes_the_oldest_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History {
+            root: dir.path().to_path_buf(),
+        };
+        for id in 1..=MAX_RECORDS as u64 {
+            fs::write(history.path(id), "{}").unwrap();
+        }
+        let id = history.add(record(json!({}), None)).unwrap();
+        assert_eq!(id, MAX_RECORDS as u64 + 1);
+        assert!(!history.path(1).exists());
+        assert!(history.path(2).exists());
+        assert_eq!(history.ids().unwrap().len(), MAX_RECORDS);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn records_a_run_redacted_with_the_digest_of_its_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = json!({
+            "kind": "tool",
+            "name": "echo",
+            "version": "1.0.0",
+            "entrypoint": "run.sh",
+            "runtime": "shell",
+            "inputs": { "type": "object" },
+            "outputs": { "type": "object" },
+            "env": { "API_TOKEN": { "secret": true } },
+        });
+        fs::write(dir.path().join("agent.json"), manifest.to_string()).unwrap();
+        fs::write(
+            dir.path().join("run.sh"),
+            "printf '{\"token\": \"%s\"}' \"$API_TOKEN\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join(".env"), "API_TOKEN=tok-123456\n").unwrap();
+        let tool = ToolPackage::new(
+            dir.path().to_path_buf(),
+            serde_json::from_value(manifest).unwrap(),
+        )
+        .unwrap();
+        let opts = crate::runner::RunOptions {
+            dotenv: Some(dir.path().join(".env")),
+            ..Default::default()
+        };
+
+        let input = json!({ "q": 1 });
+        let outcome = tool.run(&input, &opts).unwrap();
+        assert_eq!(outcome.output, Some(json!({ "token": "tok-123456" })));
+        let run = RunRecord::new(&tool, &input, &outcome).unwrap();
+        assert_eq!(run.output, Some(json!({ "token": "[redacted]" })));
+        assert_eq!(run.exit_code, Some(0));
+        assert_eq!(run.digest, package_digest(&tool).unwrap());
+
+        // Replay compares digests to notice edited code
+        fs::write(dir.path().join("run.sh"), "echo '{}'\n").unwrap();
+        assert_ne!(run.digest, package_digest(&tool).unwrap());
+    }
+}
@@ -1,5 +1,10 @@
 use crate::prelude::*;
-use std::{fs, io::Write, path::Path};
+use anyhow::bail;
+use std::{
+    fs,
+    io::Write,
+    path::{Component, Path},
+};
 
 pub fn write_atomic(path: &Path, contents: &str) -> Result<()> {
     if let Some(parent) = path.parent() {
@@ -16,3 +21,147 @@ pub fn write_atomic(path: &Path, contents: &str) -> Result<()> {
         .with_context(|| format!("renaming {} -> {}", tmp.display(), path.display()))?;
     Ok(())
 }
+
+/// Recursively copy `src` into `dst` (created if missing), keeping file permissions.
+/// Symlinks are recreated, never followed, and only when they stay inside `src`: a package
+/// must not pull files from the machine it is installed on into its own directory.
+pub fn copy_dir(src: &Path, dst: &Path) -> Result<()> {
+    copy_tree(src, dst, Path::new(""))
+}
+
+/// Copy the directory `rel` of `src` to the same place under `dst`.
+fn copy_tree(src: &Path, dst: &Path, rel: &Path) -> Result<()> {
+    let dir = dst.join(rel);
+    fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+    let from_dir = src.join(rel);
+    for entry in
+        fs::read_dir(&from_dir).with_context(|| format!("reading {}", from_dir.display()))?
+    {
+        let entry = entry?;
+        let rel = rel.join(entry.file_name());
+        let (from, to) = (src.join(&rel), dst.join(&rel));
+        // Not followed: a link is reported as a link
+        let kind = entry.file_type()?;
+        if kind.is_symlink() {
+            let target =
+                fs::read_link(&from).with_context(|| format!("reading link {}", from.display()))?;
+            if !link_stays_inside(&rel, &target) {
+                bail!(
+                    "{} links to {}, outside the package",
+                    from.display(),
+                    target.display()
+                );
+            }
+            symlink(&target, &to)?;
+        } else if kind.is_dir() {
+            copy_tree(src, dst, &rel)?;
+        } else if kind.is_file() {
+            fs::copy(&from, &to)
+                .with_context(|| format!("copying {} -> {}", from.display(), to.display()))?;
+        } else {
+            bail!("{} is not a file, directory or symlink", from.display());
+        }
+    }
+    Ok(())
+}
+
+/// Whether a link at `link` (relative to the package root) pointing at `target` resolves
+/// inside the package. Only leading `..` are allowed: after a name, `..` would step out of
+/// wherever that name leads, which may itself be a link.
+fn link_stays_inside(link: &Path, target: &Path) -> bool {
+    let mut depth = link.components().count().saturating_sub(1);
+    let mut descended = false;
+    for component in target.components() {
+        match component {
+            Component::CurDir => {}
+            Component::Normal(_) => descended = true,
+            Component::ParentDir if !descended && depth > 0 => depth -= 1,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(target, link)
+        .with_context(|| format!("linking {} -> {}", link.display(), target.display()))
+}
+
+#[cfg(not(unix))]
+fn symlink(_target: &Path, link: &Path) -> Result<()> {
+    bail!(
+        "{} is a symlink, which packages can't use here",
+        link.display()
+    )
+}
+
+/// Remove a directory tree; a missing directory is not an error.
+pub fn remove_dir_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_dir_all(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("removing {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    fn package(links: &[(&str, &str)]) -> tempfile::TempDir {
+        let src = tempfile::tempdir().unwrap();
+        fs::create_dir(src.path().join("sub")).unwrap();
+        fs::write(src.path().join("file"), "inside").unwrap();
+        for (link, target) in links {
+            symlink(target, src.path().join(link)).unwrap();
+        }
+        src
+    }
+
+    #[test]
+    fn links_inside_the_package_are_recreated() {
+        let src = package(&[("alias", "file"), ("sub/up", "../file"), ("here", "./sub")]);
+        let dst = tempfile::tempdir().unwrap();
+        copy_dir(src.path(), &dst.path().join("pkg")).unwrap();
+
+        let pkg = dst.path().join("pkg");
+        for link in ["alias", "sub/up", "here"] {
+            let meta = fs::symlink_metadata(pkg.join(link)).unwrap();
+            assert!(meta.file_type().is_symlink(), "{link} was dereferenced");
+        }
+        assert_eq!(fs::read_to_string(pkg.join("sub/up")).unwrap(), "inside");
+    }
+
+    #[test]
+    fn links_out_of_the_package_are_rejected() {
+        let outside = tempfile::tempdir().unwrap();
+        let secret = outside.path().join("id_ed25519");
+        fs::write(&secret, "private key").unwrap();
+
+        for (link, target) in [
+            ("secrets", secret.to_str().unwrap()),
+            ("up", "../id_ed25519"),
+            ("sub/up", "../../id_ed25519"),
+            // `sub/..` is only the root while `sub` is not a link; refused either way
+            ("sneaky", "sub/../../id_ed25519"),
+        ] {
+            let src = package(&[(link, target)]);
+            let dst = tempfile::tempdir().unwrap();
+            let err = copy_dir(src.path(), &dst.path().join("pkg")).unwrap_err();
+            assert!(
+                err.to_string().contains("outside the package"),
+                "{link}: {err}"
+            );
+            assert!(
+                !fs::read_dir(dst.path().join("pkg"))
+                    .unwrap()
+                    .flatten()
+                    .any(|e| fs::read_to_string(e.path()).is_ok_and(|s| s == "private key")),
+                "{link} was copied"
+            );
+        }
+    }
+}
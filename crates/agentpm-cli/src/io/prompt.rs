@@ -0,0 +1,19 @@
+use crate::prelude::*;
+use anyhow::bail;
+use std::io::{BufRead, IsTerminal, Write};
+
+/// Ask a yes/no question on the terminal (default: no).
+/// When stdin is not interactive, fail with `hint` instead of guessing.
+pub fn confirm(question: &str, hint: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        bail!("cannot ask \"{question}\": stdin is not a terminal; {hint}");
+    }
+    eprint!("{question} [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
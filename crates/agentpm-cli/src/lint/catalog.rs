@@ -0,0 +1,185 @@
+//! What agent manifests' `tools` entries can resolve to, gathered before linting so the
+//! rules themselves stay synchronous: sibling manifests in the workspace, each agent's
+//! lockfile, and (unless offline) the versions the registry publishes.
+
+use crate::lockfile::{LOCK_FILE, Lockfile};
+use crate::manifest::{MANIFEST_FILE, ToolRef};
+use crate::prelude::*;
+use semver::Version;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A manifest found next to the ones being linted.
+#[derive(Debug, Clone)]
+pub struct LocalManifest {
+    /// `None` when missing or not semver
+    pub version: Option<Version>,
+    /// Names from its own `tools`, for cycle detection
+    pub tools: Vec<String>,
+}
+
+/// What the registry said about a tool name.
+#[derive(Debug, Clone)]
+pub enum Published {
+    /// Non-yanked versions
+    Versions(Vec<Version>),
+    NotFound,
+    /// The registry couldn't be asked; nothing can be concluded
+    Unreachable,
+}
+
+#[derive(Debug, Default)]
+pub struct Catalog {
+    /// Workspace manifests by `name`
+    local: BTreeMap<String, Vec<LocalManifest>>,
+    /// Lockfiles by the directory of the agent they belong to
+    locks: BTreeMap<PathBuf, Lockfile>,
+    registry: BTreeMap<String, Published>,
+}
+
+impl Catalog {
+    /// Read the workspace around `files`: each linted manifest, the manifests in sibling
+    /// directories of every agent, and the agents' lockfiles.
+    pub fn discover(files: &[PathBuf]) -> Self {
+        let mut catalog = Self::default();
+        let mut seen = BTreeSet::new();
+        for file in files {
+            let Some(value) = read(file) else { continue };
+            catalog.add_local(file, &value, &mut seen);
+            if value.get("kind").and_then(Value::as_str) != Some("agent") {
+                continue;
+            }
+            let dir = package_dir(file);
+            if let Ok(Some(lock)) = Lockfile::read(&dir.join(LOCK_FILE)) {
+                catalog.locks.insert(normalize(&dir), lock);
+            }
+            let Ok(entries) = fs::read_dir(dir.join("..")) else {
+                continue;
+            };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let sibling = entry.path().join(MANIFEST_FILE);
+                if let Some(value) = read(&sibling) {
+                    catalog.add_local(&sibling, &value, &mut seen);
+                }
+            }
+        }
+        catalog
+    }
+
+    /// Ask the registry about every tool an agent references that isn't in the workspace.
+    pub async fn fetch(&mut self, client: &AgentPmClient, files: &[PathBuf]) {
+        let mut names = BTreeSet::new();
+        for file in files {
+            let Some(value) = read(file) else { continue };
+            if value.get("kind").and_then(Value::as_str) == Some("agent") {
+                names.extend(
+                    tool_refs(&value)
+                        .into_iter()
+                        .map(|(_, tool)| tool.name().to_string()),
+                );
+            }
+        }
+        names.retain(|name| !self.local.contains_key(name));
+
+        let lookups = names.into_iter().map(|name| async move {
+            let published = match client.tool_versions(&name).await {
+                Ok(versions) => Published::Versions(
+                    versions
+                        .iter()
+                        .filter(|v| !v.yanked)
+                        .filter_map(|v| Version::parse(&v.version).ok())
+                        .collect(),
+                ),
+                Err(SdkError::NotFound) => Published::NotFound,
+                Err(e) => {
+                    debug!("fetching versions of `{name}`: {e}");
+                    Published::Unreachable
+                }
+            };
+            (name, published)
+        });
+        self.registry
+            .extend(futures::future::join_all(lookups).await);
+    }
+
+    /// Whether any registry lookup failed, so results lean on local sources only.
+    pub fn registry_unreachable(&self) -> bool {
+        self.registry
+            .values()
+            .any(|p| matches!(p, Published::Unreachable))
+    }
+
+    pub fn local(&self, name: &str) -> &[LocalManifest] {
+        self.local.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// The lockfile next to the manifest `file`, if any.
+    pub fn lock_for(&self, file: &Path) -> Option<&Lockfile> {
+        self.locks.get(&normalize(&package_dir(file)))
+    }
+
+    /// `None` when the registry wasn't asked (offline, or the tool is local).
+    pub fn published(&self, name: &str) -> Option<&Published> {
+        self.registry.get(name)
+    }
+
+    /// Record what the registry says about `name`, as `fetch` would.
+    #[cfg(test)]
+    pub fn publish(&mut self, name: &str, published: Published) {
+        self.registry.insert(name.to_string(), published);
+    }
+
+    fn add_local(&mut self, path: &Path, value: &Value, seen: &mut BTreeSet<PathBuf>) {
+        let Some(name) = value.get("name").and_then(Value::as_str) else {
+            return;
+        };
+        if !seen.insert(normalize(path)) {
+            return;
+        }
+        self.local
+            .entry(name.to_string())
+            .or_default()
+            .push(LocalManifest {
+                version: value
+                    .get("version")
+                    .and_then(Value::as_str)
+                    .and_then(|v| Version::parse(v).ok()),
+                tools: tool_refs(value)
+                    .into_iter()
+                    .map(|(_, tool)| tool.name().to_string())
+                    .collect(),
+            });
+    }
+}
+
+/// Each readable `tools` entry of `manifest`, with its index.
+pub fn tool_refs(manifest: &Value) -> Vec<(usize, ToolRef)> {
+    let Some(Value::Array(tools)) = manifest.get("tools") else {
+        return Vec::new();
+    };
+    tools
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| Some((index, serde_json::from_value(entry.clone()).ok()?)))
+        .collect()
+}
+
+fn read(path: &Path) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn package_dir(file: &Path) -> PathBuf {
+    match file.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Canonical form when the path exists, so `./a/agent.json` and `a/agent.json` agree.
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
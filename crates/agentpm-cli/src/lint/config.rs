@@ -0,0 +1,346 @@
+//! Which rules run, and at what severity.
+//!
+//! Projects configure rules in the `[lint]` section of `agentpm.toml`, found in the
+//! current directory or the nearest parent:
+//!
+//! ```toml
+//! [lint.rules]
+//! AP001 = "allow"
+//! wildcard-permission = "deny"
+//!
+//! [[lint.overrides]]
+//! paths = ["legacy/**"]
+//! rules = { AP000 = "warn" }
+//! ```
+//!
+//! Later settings win: rule default, then `[lint.rules]`, then each matching override in
+//! order, then `--allow`/`--warn`/`--deny`. A manifest can also silence rules for itself
+//! with `"x-agentpm-lint-ignore": ["AP004"]`.
+
+use super::{Rule, Severity, rules};
+use crate::prelude::*;
+use anyhow::{anyhow, bail};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+pub const CONFIG_FILE: &str = "agentpm.toml";
+
+/// Manifest key listing rules a file opts out of.
+pub const IGNORE_KEY: &str = "x-agentpm-lint-ignore";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    #[serde(alias = "off")]
+    Allow,
+    #[serde(alias = "warning")]
+    Warn,
+    #[serde(alias = "error")]
+    Deny,
+}
+
+impl Level {
+    fn severity(self) -> Option<Severity> {
+        match self {
+            Level::Allow => None,
+            Level::Warn => Some(Severity::Warning),
+            Level::Deny => Some(Severity::Error),
+        }
+    }
+}
+
+/// `agentpm.toml`. Only `[lint]` is read here; other sections are left alone.
+#[derive(Debug, Default, Deserialize)]
+struct ProjectFile {
+    #[serde(default)]
+    lint: LintSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LintSection {
+    #[serde(default)]
+    rules: BTreeMap<String, Level>,
+    #[serde(default)]
+    overrides: Vec<OverrideSection>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OverrideSection {
+    /// Globs relative to the directory holding agentpm.toml
+    paths: Vec<String>,
+    rules: BTreeMap<String, Level>,
+}
+
+type Levels = BTreeMap<&'static str, Level>;
+
+struct Override {
+    paths: GlobSet,
+    levels: Levels,
+}
+
+/// Rule levels with every rule name resolved, ready to answer per file.
+#[derive(Default)]
+pub struct LintConfig {
+    /// Directory override globs are relative to
+    root: PathBuf,
+    levels: Levels,
+    overrides: Vec<Override>,
+    flags: Levels,
+}
+
+impl LintConfig {
+    /// Read `agentpm.toml` from `start` or its nearest parent, if any.
+    pub fn discover(start: &Path) -> Result<Self> {
+        let Some(root) = start.ancestors().find(|d| d.join(CONFIG_FILE).is_file()) else {
+            return Ok(Self {
+                root: start.to_path_buf(),
+                ..Default::default()
+            });
+        };
+        let path = root.join(CONFIG_FILE);
+        let text =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        let file: ProjectFile =
+            toml::from_str(&text).with_context(|| format!("parsing {}", path.display()))?;
+
+        let in_file = |e: anyhow::Error| e.context(format!("in {}", path.display()));
+        let overrides = file
+            .lint
+            .overrides
+            .into_iter()
+            .map(|o| {
+                Ok(Override {
+                    paths: glob_set(&o.paths)?,
+                    levels: resolve(o.rules)?,
+                })
+            })
+            .collect::<Result<_>>()
+            .map_err(in_file)?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            levels: resolve(file.lint.rules).map_err(in_file)?,
+            overrides,
+            flags: Levels::new(),
+        })
+    }
+
+    /// Apply `--allow`/`--warn`/`--deny`. A rule named by several flags takes the strictest.
+    pub fn with_flags(
+        mut self,
+        allow: &[String],
+        warn: &[String],
+        deny: &[String],
+    ) -> Result<Self> {
+        for (keys, level) in [
+            (allow, Level::Allow),
+            (warn, Level::Warn),
+            (deny, Level::Deny),
+        ] {
+            for key in keys {
+                self.flags.insert(rule_id(key)?, level);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Severity of `rule` in `file`, or `None` when the rule is off there.
+    pub fn severity(&self, rule: &Rule, file: &Path) -> Option<Severity> {
+        let mut level = self.levels.get(rule.id).copied();
+        if !self.overrides.is_empty() {
+            let relative = self.relative(file);
+            for o in &self.overrides {
+                if relative.ancestors().any(|p| o.paths.is_match(p))
+                    && let Some(l) = o.levels.get(rule.id)
+                {
+                    level = Some(*l);
+                }
+            }
+        }
+        if let Some(l) = self.flags.get(rule.id) {
+            level = Some(*l);
+        }
+        match level {
+            Some(level) => level.severity(),
+            None => Some(rule.severity),
+        }
+    }
+
+    /// `file` relative to the config directory, for matching override globs. Both sides
+    /// are resolved first, so `sub/../legacy/agent.json` still matches `legacy/**`.
+    fn relative(&self, file: &Path) -> PathBuf {
+        let absolute = std::env::current_dir()
+            .map(|cwd| cwd.join(file))
+            .unwrap_or_else(|_| file.to_path_buf());
+        let absolute = resolve_path(&absolute);
+        absolute
+            .strip_prefix(resolve_path(&self.root))
+            .map(Path::to_path_buf)
+            .unwrap_or(absolute)
+    }
+}
+
+/// The canonical path when it exists, else `path` with `.` and `..` removed lexically.
+fn resolve_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// ID of the rule `key` names, by ID or name.
+pub fn rule_id(key: &str) -> Result<&'static str> {
+    rules::find(key)
+        .map(|r| r.id)
+        .ok_or_else(|| anyhow!("unknown lint rule `{key}`. See `agentpm lint --list-rules`."))
+}
+
+fn resolve(rules: BTreeMap<String, Level>) -> Result<Levels> {
+    rules
+        .into_iter()
+        .map(|(key, level)| Ok((rule_id(&key)?, level)))
+        .collect()
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet> {
+    if patterns.is_empty() {
+        bail!("an override needs at least one entry in `paths`");
+    }
+    let mut set = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        set.add(
+            GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("invalid glob `{pattern}`"))?,
+        );
+    }
+    Ok(set.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(key: &str) -> &'static Rule {
+        rules::find(key).unwrap()
+    }
+
+    fn config(toml: &str) -> (tempfile::TempDir, LintConfig) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(CONFIG_FILE), toml).unwrap();
+        fs::create_dir_all(dir.path().join("legacy/old")).unwrap();
+        let config = LintConfig::discover(&dir.path().join("legacy/old")).unwrap();
+        (dir, config)
+    }
+
+    #[test]
+    fn later_settings_win() {
+        let (dir, config) = config(
+            r#"
+            [lint.rules]
+            AP001 = "allow"
+            empty-description = "deny"
+
+            [[lint.overrides]]
+            paths = ["./legacy/**"]
+            rules = { missing-schema = "warn", AP002 = "off" }
+
+            [[lint.overrides]]
+            paths = ["legacy/old/"]
+            rules = { AP002 = "error" }
+            "#,
+        );
+        let root = dir.path();
+        let top = root.join("agent.json");
+        let legacy = root.join("legacy/agent.json");
+        let old = root.join("legacy/old/agent.json");
+
+        assert_eq!(config.severity(rule("AP001"), &top), None);
+        assert_eq!(config.severity(rule("AP002"), &top), Some(Severity::Error));
+        assert_eq!(
+            config.severity(rule("AP001"), &legacy),
+            Some(Severity::Warning)
+        );
+        assert_eq!(config.severity(rule("AP002"), &legacy), None);
+        assert_eq!(config.severity(rule("AP002"), &old), Some(Severity::Error));
+        // Untouched rules keep their default
+        assert_eq!(config.severity(rule("AP020"), &old), Some(Severity::Error));
+
+        let config = config
+            .with_flags(&["AP002".into()], &["ap020".into()], &["AP002".into()])
+            .unwrap();
+        assert_eq!(config.severity(rule("AP002"), &top), Some(Severity::Error));
+        assert_eq!(
+            config.severity(rule("AP020"), &old),
+            Some(Severity::Warning)
+        );
+    }
+
+    #[test]
+    fn overrides_match_paths_with_dot_dot() {
+        let (dir, config) = config(
+            r#"
+            [[lint.overrides]]
+            paths = ["legacy/**"]
+            rules = { AP001 = "allow" }
+            "#,
+        );
+        let root = dir.path();
+        for file in ["sub/../legacy/agent.json", "legacy/old/../agent.json"] {
+            assert_eq!(
+                config.severity(rule("AP001"), &root.join(file)),
+                None,
+                "{file}"
+            );
+        }
+        let outside = root.join("legacy/../agent.json");
+        assert_eq!(
+            config.severity(rule("AP001"), &outside),
+            Some(Severity::Warning)
+        );
+        // `**` doesn't cross out of the directory with `..`
+        let escaped = root.join("legacy/../../agent.json");
+        assert_eq!(
+            config.severity(rule("AP001"), &escaped),
+            Some(Severity::Warning)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_rules_and_bad_globs() {
+        for toml in [
+            "[lint.rules]\nAP999 = \"deny\"\n",
+            "[lint.rules]\nAP001 = \"loud\"\n",
+            "[lint]\nrule = {}\n",
+            "[[lint.overrides]]\npaths = []\nrules = {}\n",
+            "[[lint.overrides]]\npaths = [\"legacy/[\"]\nrules = {}\n",
+        ] {
+            let dir = tempfile::tempdir().unwrap();
+            fs::write(dir.path().join(CONFIG_FILE), toml).unwrap();
+            assert!(LintConfig::discover(dir.path()).is_err(), "{toml}");
+        }
+        let err = LintConfig::default()
+            .with_flags(&[], &["no-such-rule".into()], &[])
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("unknown lint rule"), "{err}");
+    }
+}
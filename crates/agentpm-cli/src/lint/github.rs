@@ -0,0 +1,84 @@
+//! GitHub Actions workflow commands (`::error file=...,line=...::message`), which the
+//! runner turns into annotations on the changed lines.
+
+use super::{LintFileReport, Severity};
+use std::fmt::Write;
+
+pub fn render(reports: &[LintFileReport]) -> String {
+    let mut out = String::new();
+    for report in reports {
+        for issue in report.issues.iter() {
+            let command = match issue.level {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            let mut props = format!("file={}", property(report.file.trim_start_matches("./")));
+            if let Some(line) = issue.line {
+                let _ = write!(props, ",line={line}");
+            }
+            if let Some(column) = issue.column {
+                let _ = write!(props, ",col={column}");
+            }
+            if !issue.rule.is_empty() {
+                let _ = write!(props, ",title={}", property(issue.rule));
+            }
+            let _ = writeln!(out, "::{command} {props}::{}", data(&issue.message));
+        }
+    }
+    out
+}
+
+/// Escape a message the way the runner unescapes it.
+fn data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Property values additionally can't hold the `:` and `,` that delimit them.
+fn property(text: &str) -> String {
+    data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{LintFileReport, LintIssue};
+
+    #[test]
+    fn renders_escaped_workflow_commands() {
+        let issue = |rule, level, line: Option<usize>, message: &str| LintIssue {
+            file: String::new(),
+            rule,
+            level,
+            message: message.into(),
+            instance_path: String::new(),
+            schema_path: String::new(),
+            line,
+            column: line.map(|_| 7),
+            fix: None,
+            span: None,
+        };
+        let reports = [LintFileReport {
+            file: "./a,b/agent:1.json".into(),
+            ok: false,
+            issues: vec![
+                issue(
+                    "AP000",
+                    Severity::Error,
+                    Some(4),
+                    "100% wrong\r\nsee: docs, faq",
+                ),
+                issue("", Severity::Warning, None, "unreadable"),
+            ],
+            fixed: 0,
+            source: String::new(),
+            diff: None,
+        }];
+        assert_eq!(
+            render(&reports),
+            "::error file=a%2Cb/agent%3A1.json,line=4,col=7,title=AP000::100%25 wrong%0D%0Asee: docs, faq\n\
+             ::warning file=a%2Cb/agent%3A1.json::unreadable\n"
+        );
+    }
+}
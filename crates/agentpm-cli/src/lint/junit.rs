@@ -0,0 +1,107 @@
+//! JUnit XML output: one test case per manifest, one failure per issue that fails it.
+
+use super::{LintFileReport, Severity};
+use crate::util::junit;
+use std::time::Duration;
+
+/// Issues that don't fail the file (warnings without `--strict`) go to `<system-err>`.
+pub fn render(reports: &[LintFileReport], strict: bool) -> String {
+    let cases = reports
+        .iter()
+        .map(|report| {
+            let mut case = junit::Case {
+                name: report.file.clone(),
+                classname: "agentpm.lint".into(),
+                time: Duration::ZERO,
+                ..Default::default()
+            };
+            for issue in report.issues.iter() {
+                let location = match (issue.line, issue.column) {
+                    (Some(line), Some(column)) => format!("{}:{line}:{column}", report.file),
+                    _ => report.file.clone(),
+                };
+                let rule = if issue.rule.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}: ", issue.rule)
+                };
+                if strict || issue.level == Severity::Error {
+                    case.failures.push((
+                        format!("{rule}{}", issue.message),
+                        format!("{location}\n{rule}{}", issue.message),
+                    ));
+                } else {
+                    case.stderr
+                        .push_str(&format!("warning: {location}: {rule}{}\n", issue.message));
+                }
+            }
+            case
+        })
+        .collect();
+    junit::render(&[junit::Suite {
+        name: "agentpm lint".into(),
+        cases,
+    }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{LintFileReport, LintIssue};
+
+    fn issue(rule: &'static str, level: Severity, line: Option<usize>) -> LintIssue {
+        LintIssue {
+            file: String::new(),
+            rule,
+            level,
+            message: "name <Demo> & co".into(),
+            instance_path: String::new(),
+            schema_path: String::new(),
+            line,
+            column: line.map(|_| 3),
+            fix: None,
+            span: None,
+        }
+    }
+
+    fn reports() -> Vec<LintFileReport> {
+        vec![LintFileReport {
+            file: "demo/agent.json".into(),
+            ok: false,
+            issues: vec![
+                issue("AP000", Severity::Error, Some(2)),
+                issue("AP005", Severity::Warning, None),
+            ],
+            fixed: 0,
+            source: String::new(),
+            diff: None,
+        }]
+    }
+
+    #[test]
+    fn errors_fail_and_warnings_go_to_system_err() {
+        let xml = render(&reports(), false);
+        assert!(
+            xml.contains(r#"<testsuites tests="1" failures="1""#),
+            "{xml}"
+        );
+        assert!(
+            xml.contains(
+                r#"<failure message="AP000: name &lt;Demo&gt; &amp; co">demo/agent.json:2:3"#
+            ),
+            "{xml}"
+        );
+        assert!(
+            xml.contains("<system-err>warning: demo/agent.json: AP005: name &lt;Demo&gt;"),
+            "{xml}"
+        );
+        assert_eq!(xml.matches("<failure").count(), 1, "{xml}");
+    }
+
+    #[test]
+    fn strict_fails_on_warnings_too() {
+        let xml = render(&reports(), true);
+        assert_eq!(xml.matches("<failure").count(), 2, "{xml}");
+        assert!(!xml.contains("<system-err>"), "{xml}");
+    }
+}
@@ -0,0 +1,597 @@
+//! Manifest linting: a registry of rules run over each agent.json.
+//!
+//! Every rule has a stable ID (`AP001`), a default severity and, optionally, an autofix
+//! expressed as text edits. Adding a rule means writing its check in `rules/` and listing
+//! it in `RULES`; the engine here stays untouched.
+
+pub mod catalog;
+pub mod config;
+pub mod github;
+pub mod junit;
+pub mod rules;
+pub mod sarif;
+pub mod schema_cache;
+
+use crate::io::fs::write_atomic;
+use crate::prelude::*;
+use crate::util::json_edit::{self, TextEdit};
+use crate::util::json_span::{self, Spanned};
+use crate::util::{load_json, load_schema_value};
+use anyhow::Context as _;
+use jsonschema::{Draft, JSONSchema};
+use serde::Serialize;
+use serde_json::Value;
+use similar::TextDiff;
+use std::{
+    collections::BTreeSet,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+pub use catalog::Catalog;
+pub use config::LintConfig;
+pub use rules::RULES;
+pub use schema_cache::SchemaCache;
+
+/// Fixes can unblock other fixes; this bounds how many rounds `--fix` takes.
+const MAX_FIX_PASSES: usize = 10;
+
+/// Where editors and `--fix` point `$schema`.
+pub const SCHEMA_URL: &str = "https://raw.githubusercontent.com/agentpm-dev/cli/refs/heads/main/schemas/agentpm.manifest.schema.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A lint rule. Rules are plain data plus a check function, listed in `RULES`.
+pub struct Rule {
+    /// Stable ID users refer to, e.g. `AP001`
+    pub id: &'static str,
+    /// Short kebab-case name, e.g. `missing-schema`
+    pub name: &'static str,
+    pub description: &'static str,
+    pub severity: Severity,
+    /// Whether some findings carry an autofix
+    pub fixable: bool,
+    pub check: fn(&Context) -> Vec<Finding>,
+}
+
+/// What a rule gets to look at.
+pub struct Context<'a> {
+    /// The manifest being linted; its directory is the package
+    pub file: &'a Path,
+    pub value: &'a Value,
+    /// The file as written, for autofix edits
+    pub source: &'a str,
+    /// Span-preserving parse of `source`
+    pub spans: &'a Spanned,
+    pub schema: &'a JSONSchema,
+    /// What `tools` entries can resolve to
+    pub catalog: &'a Catalog,
+}
+
+/// One problem a rule found. The engine adds the rule, severity and location.
+#[derive(Debug, Default)]
+pub struct Finding {
+    pub message: String,
+    /// JSON pointer to the offending value
+    pub instance_path: String,
+    pub schema_path: String,
+    pub fix: Option<Fix>,
+}
+
+impl Finding {
+    pub fn at(instance_path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            instance_path: instance_path.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Edits that resolve a finding.
+#[derive(Debug, Clone)]
+pub struct Fix {
+    pub description: String,
+    pub edits: Vec<TextEdit>,
+}
+
+#[derive(Serialize)]
+pub struct LintIssue {
+    file: String,
+    /// ID of the rule that raised the issue (empty for unreadable files)
+    #[serde(skip_serializing_if = "str::is_empty")]
+    rule: &'static str,
+    level: Severity,
+    message: String,
+    instance_path: String,
+    schema_path: String,
+    /// 1-based position of the offending value in the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+    /// What `--fix` would do about it, if anything
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "fix_description"
+    )]
+    fix: Option<Fix>,
+    /// Byte range of the offending value, for the code frame
+    #[serde(skip)]
+    span: Option<Range<usize>>,
+}
+
+#[derive(Serialize)]
+pub struct LintFileReport {
+    file: String,
+    pub ok: bool,
+    /// After `--fix`, what is left, located in the file as rewritten
+    issues: Vec<LintIssue>,
+    /// How many issues `--fix` resolved
+    #[serde(skip_serializing_if = "is_zero")]
+    pub fixed: usize,
+    /// File contents, for code frames
+    #[serde(skip)]
+    source: String,
+    /// What `--fix --dry-run` would change, as a unified diff
+    #[serde(skip)]
+    pub diff: Option<String>,
+}
+
+/// What `--fix` does with the fixed text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FixMode {
+    #[default]
+    Off,
+    /// Write it back to the manifest
+    Write,
+    /// Only show the diff
+    DryRun,
+}
+
+/// The manifest schema, compiled once, and how to judge files against it.
+pub struct Linter {
+    compiled: JSONSchema,
+    config: LintConfig,
+    strict: bool,
+    fix: FixMode,
+}
+
+impl Linter {
+    /// Load the schema from `schema` (a URL or path), else the repo copy, else the published
+    /// one. URLs go through `schemas`, so they work offline once fetched.
+    pub fn new(
+        schema: Option<String>,
+        schemas: &SchemaCache,
+        config: LintConfig,
+        strict: bool,
+        fix: FixMode,
+    ) -> Result<Self> {
+        // Resolve schema
+        let schema_source = schema.unwrap_or_else(|| {
+            let local_path = PathBuf::from("schemas/agentpm.manifest.schema.json");
+            if local_path.exists() {
+                local_path.to_string_lossy().into_owned()
+            } else {
+                SCHEMA_URL.to_string()
+            }
+        });
+        let schema_value =
+            if schema_source.starts_with("http://") || schema_source.starts_with("https://") {
+                schemas.load(&schema_source)?
+            } else {
+                load_schema_value(&schema_source)?
+            };
+        let schema_static: &'static serde_json::Value = Box::leak(Box::new(schema_value));
+        let compiled = JSONSchema::options()
+            .with_draft(Draft::Draft202012)
+            .compile(schema_static)?;
+        Ok(Self {
+            compiled,
+            config,
+            strict,
+            fix,
+        })
+    }
+
+    /// Run every rule over one manifest, fixing what can be fixed when asked. Unreadable
+    /// JSON is reported as an issue; only a failed `--fix` write is an error.
+    pub fn lint_file(&self, file: &Path, catalog: &Catalog) -> Result<LintFileReport> {
+        let mut source = match load_json(file) {
+            Ok((_, source)) => source,
+            Err(e) => return Ok(unreadable(file, e)),
+        };
+        let mut issues = self.check(file, &source, catalog)?;
+        let mut fixed_count = 0;
+        let mut diff = None;
+
+        if self.fix != FixMode::Off && issues.iter().any(|i| i.fix.is_some()) {
+            let fixed = self.fix_all(file, &source, catalog)?;
+            if self.fix == FixMode::DryRun {
+                let name = file.to_string_lossy();
+                diff = Some(
+                    TextDiff::from_lines(&source, &fixed)
+                        .unified_diff()
+                        .context_radius(3)
+                        .header(&name, &name)
+                        .to_string(),
+                );
+            } else {
+                // Report what is left against the file as it now is, so positions hold.
+                // Issues that no longer occur were fixed, even when another rule's fix
+                // resolved them
+                let remaining = self.check(file, &fixed, catalog)?;
+                fixed_count = issues
+                    .iter()
+                    .filter(|i| {
+                        !remaining
+                            .iter()
+                            .any(|r| r.rule == i.rule && r.message == i.message)
+                    })
+                    .count();
+                if fixed != source {
+                    write_atomic(file, &fixed).with_context(|| {
+                        format!("Failed to write fixed file {}", file.display())
+                    })?;
+                }
+                issues = remaining;
+                source = fixed;
+            }
+        }
+
+        let has_error = issues.iter().any(|i| i.level == Severity::Error);
+        let has_warning = issues.iter().any(|i| i.level == Severity::Warning);
+
+        let ok = if self.strict {
+            // strict: any warning or error fails
+            !has_error && !has_warning
+        } else {
+            // non-strict: errors fail, warnings allowed
+            !has_error
+        };
+        Ok(LintFileReport {
+            file: file.to_string_lossy().to_string(),
+            ok,
+            issues,
+            fixed: fixed_count,
+            source,
+            diff,
+        })
+    }
+
+    /// Apply fixes until none apply. Fixes whose edits overlap an earlier one's wait for
+    /// the next pass, which sees the text the earlier fix left.
+    fn fix_all(&self, file: &Path, source: &str, catalog: &Catalog) -> Result<String> {
+        let mut text = source.to_string();
+        for _ in 0..MAX_FIX_PASSES {
+            let mut edits: Vec<TextEdit> = Vec::new();
+            for fix in self
+                .check(file, &text, catalog)?
+                .into_iter()
+                .filter_map(|i| i.fix)
+            {
+                let clash = fix
+                    .edits
+                    .iter()
+                    .any(|e| edits.iter().any(|o| overlaps(&e.range, &o.range)));
+                if !clash {
+                    edits.extend(fix.edits);
+                }
+            }
+            if edits.is_empty() {
+                break;
+            }
+            text = json_edit::apply(&text, edits);
+        }
+        Ok(text)
+    }
+
+    /// Every enabled rule's findings on `source`, as issues.
+    fn check(&self, file: &Path, source: &str, catalog: &Catalog) -> Result<Vec<LintIssue>> {
+        let value: Value = serde_json::from_str(source)
+            .with_context(|| format!("Failed to parse JSON in {}", file.display()))?;
+        let spans = json_span::parse(source)
+            .with_context(|| format!("Failed to parse JSON in {}", file.display()))?;
+        let cx = Context {
+            file,
+            value: &value,
+            source,
+            spans: &spans,
+            schema: &self.compiled,
+            catalog,
+        };
+
+        let mut issues = Vec::new();
+        let ignored = inline_ignores(file, &value, source, &spans, &mut issues);
+        for rule in RULES {
+            if ignored.contains(rule.id) {
+                continue;
+            }
+            let Some(severity) = self.config.severity(rule, file) else {
+                continue;
+            };
+            for finding in (rule.check)(&cx) {
+                let span = spans
+                    .pointer(&finding.instance_path)
+                    .map(|s| s.span.clone());
+                let (line, column) = span
+                    .as_ref()
+                    .map(|s| json_span::line_col(source, s.start))
+                    .unzip();
+                issues.push(LintIssue {
+                    file: file.to_string_lossy().to_string(),
+                    rule: rule.id,
+                    level: severity,
+                    message: finding.message,
+                    instance_path: finding.instance_path,
+                    schema_path: finding.schema_path,
+                    line,
+                    column,
+                    fix: finding.fix,
+                    span,
+                });
+            }
+        }
+        Ok(issues)
+    }
+}
+
+/// Rules the manifest silences with `x-agentpm-lint-ignore`. Names that match no rule
+/// are reported rather than dropped, so a typo doesn't silently keep a rule on.
+fn inline_ignores(
+    file: &Path,
+    value: &Value,
+    source: &str,
+    spans: &Spanned,
+    issues: &mut Vec<LintIssue>,
+) -> BTreeSet<&'static str> {
+    let mut ignored = BTreeSet::new();
+    let Some(Value::Array(keys)) = value.get(config::IGNORE_KEY) else {
+        return ignored;
+    };
+    for (index, key) in keys.iter().enumerate() {
+        let Some(key) = key.as_str() else { continue };
+        match config::rule_id(key) {
+            Ok(id) => {
+                ignored.insert(id);
+            }
+            Err(e) => {
+                let instance_path = format!("/{}/{index}", config::IGNORE_KEY);
+                let span = spans.pointer(&instance_path).map(|s| s.span.clone());
+                let (line, column) = span
+                    .as_ref()
+                    .map(|s| json_span::line_col(source, s.start))
+                    .unzip();
+                issues.push(LintIssue {
+                    file: file.to_string_lossy().to_string(),
+                    rule: "",
+                    level: Severity::Warning,
+                    message: e.to_string(),
+                    instance_path,
+                    schema_path: String::new(),
+                    line,
+                    column,
+                    fix: None,
+                    span,
+                });
+            }
+        }
+    }
+    ignored
+}
+
+fn fix_description<S: serde::Serializer>(
+    fix: &Option<Fix>,
+    s: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match fix {
+        Some(fix) => s.serialize_str(&fix.description),
+        None => s.serialize_none(),
+    }
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// Whether two edits touch the same bytes; insertions count as one byte wide.
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end.max(b.start + 1) && b.start < a.end.max(a.start + 1)
+}
+
+/// Report for a file that could not be read or parsed, pointing at the parse error.
+fn unreadable(file: &Path, e: anyhow::Error) -> LintFileReport {
+    let source = fs::read_to_string(file).unwrap_or_default();
+    let (line, column) = match e.root_cause().downcast_ref::<serde_json::Error>() {
+        Some(json) => (Some(json.line()), Some(json.column())),
+        None => (None, None),
+    };
+    LintFileReport {
+        file: file.to_string_lossy().to_string(),
+        ok: false,
+        issues: vec![LintIssue {
+            file: file.to_string_lossy().to_string(),
+            rule: "",
+            level: Severity::Error,
+            message: format!("Failed to parse JSON: {}", e.root_cause()),
+            instance_path: String::new(),
+            schema_path: String::new(),
+            line,
+            column,
+            fix: None,
+            span: line
+                .zip(column)
+                .map(|(l, c)| offset_of(&source, l, c))
+                .map(|at| at..at),
+        }],
+        fixed: 0,
+        source,
+        diff: None,
+    }
+}
+
+/// Print reports as `✓ file` / `✗ file` followed by their issues.
+pub fn print_pretty(reports: &[LintFileReport]) {
+    for r in reports {
+        if r.ok {
+            println!("✓ {}", r.file);
+        } else {
+            println!("✗ {}", r.file);
+        }
+        if r.fixed > 0 {
+            println!("  fixed {} issue(s)", r.fixed);
+        }
+        for i in &r.issues {
+            let badge = match i.level {
+                Severity::Error => "ERROR",
+                Severity::Warning => "WARN ",
+            };
+            if i.rule.is_empty() {
+                println!("  [{badge}] {}", i.message);
+            } else {
+                println!("  [{badge}] {}: {}", i.rule, i.message);
+            }
+            if let Some(span) = &i.span {
+                print_frame(&r.file, &r.source, span);
+            }
+            if !i.instance_path.is_empty() {
+                println!("        at instance {}", i.instance_path);
+            }
+            if !i.schema_path.is_empty() {
+                println!("        vs schema  {}", i.schema_path);
+            }
+            if let Some(fix) = &i.fix {
+                println!("        fix: {} (run with --fix)", fix.description);
+            }
+        }
+    }
+}
+
+/// A rustc-style excerpt: the line holding `span`, with carets under it.
+fn print_frame(file: &str, source: &str, span: &Range<usize>) {
+    let (line, column) = json_span::line_col(source, span.start);
+    let Some(text) = source.lines().nth(line - 1) else {
+        return;
+    };
+    let text = text.trim_end_matches('\r');
+    // Keep tabs so the carets line up under them
+    let pad: String = text
+        .chars()
+        .take(column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let rest = text.chars().count().saturating_sub(column - 1);
+    let width = source
+        .get(span.clone())
+        .map_or(1, |s| s.lines().next().unwrap_or("").chars().count())
+        .clamp(1, rest.max(1));
+
+    let gutter = " ".repeat(line.to_string().len());
+    println!("   {gutter}--> {file}:{line}:{column}");
+    println!("   {gutter} |");
+    println!("   {line} | {text}");
+    println!("   {gutter} | {pad}{}", "^".repeat(width));
+}
+
+/// Byte offset of a 1-based line and column (as serde_json reports them).
+fn offset_of(source: &str, line: usize, column: usize) -> usize {
+    let start: usize = source
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let rest = &source[start.min(source.len())..];
+    start
+        + rest
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(rest.len(), |(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::MANIFEST_SCHEMA;
+    use serde_json::json;
+
+    fn linter() -> Linter {
+        let schema: &'static Value = Box::leak(Box::new(
+            serde_json::from_str(MANIFEST_SCHEMA).expect("embedded schema is valid JSON"),
+        ));
+        Linter {
+            compiled: JSONSchema::options()
+                .with_draft(Draft::Draft202012)
+                .compile(schema)
+                .expect("embedded schema compiles"),
+            config: LintConfig::default(),
+            strict: false,
+            fix: FixMode::Write,
+        }
+    }
+
+    /// Findings of `rule` alone on `source`, linted as `file`.
+    pub(crate) fn check_rule(
+        rule: &Rule,
+        file: &Path,
+        source: &str,
+        catalog: &Catalog,
+    ) -> Vec<Finding> {
+        let value: Value = serde_json::from_str(source).unwrap();
+        let spans = json_span::parse(source).unwrap();
+        (rule.check)(&Context {
+            file,
+            value: &value,
+            source,
+            spans: &spans,
+            schema: &linter().compiled,
+            catalog,
+        })
+    }
+
+    #[test]
+    fn overlapping_ranges() {
+        assert!(overlaps(&(0..4), &(2..6)));
+        assert!(overlaps(&(2..6), &(0..4)));
+        assert!(!overlaps(&(0..2), &(2..4)));
+        assert!(!overlaps(&(2..4), &(0..2)));
+        // Insertions clash with edits around them and with each other
+        assert!(overlaps(&(3..3), &(0..6)));
+        assert!(overlaps(&(3..3), &(3..3)));
+        assert!(!overlaps(&(2..2), &(0..2)));
+    }
+
+    #[test]
+    fn fix_all_defers_overlapping_fixes_to_later_passes() {
+        // AP035 rewrites every entry, AP031 drops the repeat and AP034 moves entries
+        // around: all on the same bytes, so only one of them can go per pass
+        let source = r#"{"kind": "agent", "name": "demo", "version": "0.1.0", "description": "d", "tools": ["b@^1", "a@^1", "b@^1"]}"#;
+        let file = Path::new("demo/agent.json");
+        let catalog = Catalog::default();
+        let linter = linter();
+
+        let fixed = linter.fix_all(file, source, &catalog).unwrap();
+        let value: Value = serde_json::from_str(&fixed).unwrap();
+        assert_eq!(value["$schema"], SCHEMA_URL);
+        assert_eq!(
+            value["tools"],
+            json!([
+                { "name": "a", "version": "^1" },
+                { "name": "b", "version": "^1" },
+            ])
+        );
+
+        let left = linter.check(file, &fixed, &catalog).unwrap();
+        let fixable: Vec<&str> = left
+            .iter()
+            .filter(|i| i.fix.is_some())
+            .map(|i| i.message.as_str())
+            .collect();
+        assert!(fixable.is_empty(), "fixable issues left: {fixable:?}");
+    }
+}
@@ -0,0 +1,326 @@
+//! Rules that look past the manifest at the package directory, so a missing or
+//! unpublishable entrypoint shows up in lint rather than at publish time.
+
+use crate::lint::{Context, Finding, Rule, Severity};
+use crate::runner::runtime::{self, Runtime};
+use ignore::gitignore::GitignoreBuilder;
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+
+/// Files listed here are left out of the published package (gitignore syntax).
+pub const IGNORE_FILE: &str = ".agentpmignore";
+
+pub const ENTRYPOINT_MISSING: Rule = Rule {
+    id: "AP020",
+    name: "entrypoint-missing",
+    description: "`entrypoint` must name a file that exists, relative to agent.json.",
+    severity: Severity::Error,
+    fixable: false,
+    check: entrypoint_missing,
+};
+
+fn entrypoint_missing(cx: &Context) -> Vec<Finding> {
+    let Some(entry) = Entrypoint::of(cx) else {
+        return Vec::new();
+    };
+    if entry.path.is_file() {
+        return Vec::new();
+    }
+    let problem = if entry.path.is_dir() {
+        "is a directory"
+    } else {
+        "does not exist"
+    };
+    vec![Finding::at(
+        "/entrypoint",
+        format!("entrypoint {} {problem}", entry.path.display()),
+    )]
+}
+
+pub const ENTRYPOINT_OUTSIDE: Rule = Rule {
+    id: "AP021",
+    name: "entrypoint-outside-package",
+    description: "`entrypoint` must stay inside the package directory; nothing outside it is published.",
+    severity: Severity::Error,
+    fixable: false,
+    check: entrypoint_outside,
+};
+
+fn entrypoint_outside(cx: &Context) -> Vec<Finding> {
+    let Some(entry) = Entrypoint::of(cx) else {
+        return Vec::new();
+    };
+    let relative = Path::new(entry.raw);
+    let escapes = relative.has_root()
+        || relative
+            .components()
+            .try_fold(0usize, |depth, c| match c {
+                Component::ParentDir => depth.checked_sub(1),
+                Component::Normal(_) => Some(depth + 1),
+                _ => Some(depth),
+            })
+            .is_none();
+    // A symlink can point outside even when the path itself doesn't
+    let links_out = !escapes
+        && entry
+            .path
+            .canonicalize()
+            .ok()
+            .zip(entry.dir.canonicalize().ok())
+            .is_some_and(|(target, dir)| !target.starts_with(dir));
+    if !escapes && !links_out {
+        return Vec::new();
+    }
+    vec![Finding::at(
+        "/entrypoint",
+        format!(
+            "entrypoint `{}` {} the package directory",
+            entry.raw,
+            if escapes {
+                "is outside"
+            } else {
+                "links outside"
+            }
+        ),
+    )]
+}
+
+pub const ENTRYPOINT_NOT_STARTABLE: Rule = Rule {
+    id: "AP022",
+    name: "entrypoint-not-startable",
+    description: "The entrypoint needs a `runtime`, a shebang, a known extension (.py, .js, .sh) or the executable bit.",
+    severity: Severity::Error,
+    fixable: false,
+    check: entrypoint_not_startable,
+};
+
+fn entrypoint_not_startable(cx: &Context) -> Vec<Finding> {
+    let Some(entry) = Entrypoint::of(cx) else {
+        return Vec::new();
+    };
+    // A bad `runtime` value is AP000's to report
+    let declared = match cx.value.get("runtime") {
+        Some(value) => match serde_json::from_value::<Runtime>(value.clone()) {
+            Ok(runtime) => Some(runtime),
+            Err(_) => return Vec::new(),
+        },
+        None => None,
+    };
+    if !entry.path.is_file() || runtime::is_startable(&entry.path, declared) {
+        return Vec::new();
+    }
+    vec![Finding::at(
+        "/entrypoint",
+        format!(
+            "entrypoint {} is not executable and its runtime can't be detected; \
+             run `chmod +x` on it or set `runtime`",
+            entry.path.display()
+        ),
+    )]
+}
+
+pub const ENTRYPOINT_IGNORED: Rule = Rule {
+    id: "AP023",
+    name: "entrypoint-ignored",
+    description: "The entrypoint must not be excluded by .agentpmignore, or the published package can't run.",
+    severity: Severity::Error,
+    fixable: false,
+    check: entrypoint_ignored,
+};
+
+fn entrypoint_ignored(cx: &Context) -> Vec<Finding> {
+    let Some(entry) = Entrypoint::of(cx) else {
+        return Vec::new();
+    };
+    let ignore_file = entry.dir.join(IGNORE_FILE);
+    if !ignore_file.is_file() {
+        return Vec::new();
+    }
+    let mut builder = GitignoreBuilder::new(&entry.dir);
+    if builder.add(&ignore_file).is_some() {
+        return Vec::new();
+    }
+    let Ok(matcher) = builder.build() else {
+        return Vec::new();
+    };
+    // Paths leaving the directory are AP021's to report
+    let relative = Path::new(entry.raw.trim_start_matches("./"));
+    if relative.has_root()
+        || relative.components().any(|c| c == Component::ParentDir)
+        || !matcher
+            .matched_path_or_any_parents(relative, false)
+            .is_ignore()
+    {
+        return Vec::new();
+    }
+    vec![Finding::at(
+        "/entrypoint",
+        format!("entrypoint `{}` is excluded by {IGNORE_FILE}", entry.raw),
+    )]
+}
+
+/// A tool's `entrypoint`, as written and resolved against the manifest's directory.
+struct Entrypoint<'a> {
+    raw: &'a str,
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl<'a> Entrypoint<'a> {
+    fn of(cx: &Context<'a>) -> Option<Self> {
+        if cx.value.get("kind").and_then(Value::as_str) != Some("tool") {
+            return None;
+        }
+        let raw = cx.value.get("entrypoint")?.as_str()?;
+        if raw.trim().is_empty() {
+            return None;
+        }
+        let dir = match cx.file.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        Some(Self {
+            raw,
+            path: dir.join(raw),
+            dir,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{Catalog, tests::check_rule};
+    use serde_json::json;
+    use std::fs;
+
+    /// Messages `rule` gives a tool package in `dir` whose manifest has `extra` merged in.
+    fn messages(rule: &Rule, dir: &Path, extra: Value) -> Vec<String> {
+        let mut manifest = json!({ "kind": "tool", "name": "t", "version": "0.1.0" });
+        manifest
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        check_rule(
+            rule,
+            &dir.join("agent.json"),
+            &manifest.to_string(),
+            &Catalog::default(),
+        )
+        .into_iter()
+        .map(|f| f.message)
+        .collect()
+    }
+
+    #[test]
+    fn entrypoint_must_be_a_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/run.py"), "").unwrap();
+        let check = |entry: &str| {
+            messages(
+                &ENTRYPOINT_MISSING,
+                dir.path(),
+                json!({ "entrypoint": entry }),
+            )
+        };
+
+        assert!(check("src/run.py").is_empty());
+        assert!(check("./src/../src/run.py").is_empty());
+        assert!(check("src")[0].ends_with("is a directory"));
+        assert!(check("nope.py")[0].ends_with("does not exist"));
+        // Only tools have entrypoints
+        let agent = messages(
+            &ENTRYPOINT_MISSING,
+            dir.path(),
+            json!({ "kind": "agent", "entrypoint": "nope.py" }),
+        );
+        assert!(agent.is_empty());
+    }
+
+    #[test]
+    fn entrypoint_must_stay_in_the_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("tool");
+        fs::create_dir_all(package.join("src")).unwrap();
+        fs::write(dir.path().join("outside.py"), "").unwrap();
+        let check = |entry: &str| {
+            messages(
+                &ENTRYPOINT_OUTSIDE,
+                &package,
+                json!({ "entrypoint": entry }),
+            )
+        };
+
+        assert!(check("src/../run.py").is_empty());
+        for escaping in ["../outside.py", "src/../../outside.py", "/etc/passwd"] {
+            assert_eq!(
+                check(escaping),
+                [format!(
+                    "entrypoint `{escaping}` is outside the package directory"
+                )]
+            );
+        }
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("../outside.py", package.join("link.py")).unwrap();
+            assert_eq!(
+                check("link.py"),
+                ["entrypoint `link.py` links outside the package directory"]
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn entrypoint_must_be_startable() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("run"), "plain text\n").unwrap();
+        fs::write(dir.path().join("script"), "#!/bin/sh\necho\n").unwrap();
+        fs::write(dir.path().join("run.py"), "").unwrap();
+        fs::write(dir.path().join("binary"), "").unwrap();
+        fs::set_permissions(dir.path().join("binary"), fs::Permissions::from_mode(0o755)).unwrap();
+        let check = |extra: Value| messages(&ENTRYPOINT_NOT_STARTABLE, dir.path(), extra);
+
+        assert_eq!(check(json!({ "entrypoint": "run" })).len(), 1);
+        for startable in [
+            json!({ "entrypoint": "run", "runtime": "python" }),
+            json!({ "entrypoint": "script" }),
+            json!({ "entrypoint": "run.py" }),
+            json!({ "entrypoint": "binary" }),
+            // AP000 and AP020 report these
+            json!({ "entrypoint": "run", "runtime": "cobol" }),
+            json!({ "entrypoint": "missing" }),
+        ] {
+            assert!(check(startable.clone()).is_empty(), "{startable}");
+        }
+    }
+
+    #[test]
+    fn entrypoint_must_not_be_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = |entry: &str| {
+            messages(
+                &ENTRYPOINT_IGNORED,
+                dir.path(),
+                json!({ "entrypoint": entry }),
+            )
+        };
+        assert!(check("src/run.py").is_empty(), "no ignore file");
+
+        fs::write(
+            dir.path().join(IGNORE_FILE),
+            "*.log\nsrc/\nbuild/*\n!build/keep.py\n",
+        )
+        .unwrap();
+        assert_eq!(
+            check("./src/run.py"),
+            ["entrypoint `./src/run.py` is excluded by .agentpmignore"]
+        );
+        assert_eq!(check("build/out.py").len(), 1);
+        for kept in ["run.py", "build/keep.py", "../src/run.py"] {
+            assert!(check(kept).is_empty(), "{kept}");
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! Rules about `inputs` and `outputs`. The manifest schema only requires them to be
+//! objects; these check they are JSON Schemas callers can actually rely on.
+
+use crate::assets::META_SCHEMA_2020_12;
+use crate::lint::{Context, Finding, Rule, Severity};
+use crate::util::schema_io::compile_schema;
+use jsonschema::{Draft, JSONSchema};
+use serde_json::{Map, Value};
+use std::sync::LazyLock;
+
+/// The draft 2020-12 meta-schema. `jsonschema` barely follows the `$dynamicRef`s its
+/// bundled copy is built on, so nested schemas would go unchecked; ours merges the
+/// vocabularies into one document that plain `$ref`s back to itself.
+static META_SCHEMA: LazyLock<JSONSchema> = LazyLock::new(|| {
+    let schema: Value =
+        serde_json::from_str(META_SCHEMA_2020_12).expect("bundled meta-schema is valid JSON");
+    JSONSchema::options()
+        .with_draft(Draft::Draft202012)
+        .compile(&schema)
+        .expect("bundled meta-schema compiles")
+});
+
+/// Draft 2020-12 keywords, plus the draft-07 ones still common in the wild.
+const KEYWORDS: &[&str] = &[
+    // core
+    "$schema",
+    "$id",
+    "$ref",
+    "$defs",
+    "$anchor",
+    "$dynamicRef",
+    "$dynamicAnchor",
+    "$vocabulary",
+    "$comment",
+    // applicators
+    "prefixItems",
+    "items",
+    "contains",
+    "additionalProperties",
+    "properties",
+    "patternProperties",
+    "dependentSchemas",
+    "propertyNames",
+    "if",
+    "then",
+    "else",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+    // validation
+    "type",
+    "enum",
+    "const",
+    "multipleOf",
+    "maximum",
+    "exclusiveMaximum",
+    "minimum",
+    "exclusiveMinimum",
+    "maxLength",
+    "minLength",
+    "pattern",
+    "maxItems",
+    "minItems",
+    "uniqueItems",
+    "maxContains",
+    "minContains",
+    "maxProperties",
+    "minProperties",
+    "required",
+    "dependentRequired",
+    // annotations and content
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+    "format",
+    "contentEncoding",
+    "contentMediaType",
+    "contentSchema",
+    // draft-07
+    "definitions",
+    "dependencies",
+    "additionalItems",
+];
+
+/// Keywords that constrain a value as well as `type` does.
+const TYPE_LIKE: &[&str] = &["type", "$ref", "enum", "const", "allOf", "anyOf", "oneOf"];
+
+pub const INVALID_IO_SCHEMA: Rule = Rule {
+    id: "AP010",
+    name: "invalid-io-schema",
+    description: "`inputs` and `outputs` must be valid JSON Schemas (draft 2020-12).",
+    severity: Severity::Error,
+    fixable: false,
+    check: invalid_io_schema,
+};
+
+fn invalid_io_schema(cx: &Context) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (key, schema) in io_schemas(cx.value) {
+        if let Err(errors) = META_SCHEMA.validate(schema) {
+            // The meta-schema's own paths would only confuse next to the manifest schema's
+            findings.extend(
+                errors.map(|e| Finding::at(format!("/{key}{}", e.instance_path), e.to_string())),
+            );
+        } else if let Err(e) = compile_schema(schema) {
+            findings.push(Finding::at(format!("/{key}"), format!("`{key}`: {e}")));
+        }
+    }
+    findings
+}
+
+pub const UNKNOWN_KEYWORD: Rule = Rule {
+    id: "AP011",
+    name: "unknown-schema-keyword",
+    description: "Unknown keywords in `inputs`/`outputs` are ignored by validators, so typos go unnoticed. Prefix extensions with `x-`.",
+    severity: Severity::Warning,
+    fixable: false,
+    check: unknown_keyword,
+};
+
+fn unknown_keyword(cx: &Context) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for node in nodes(cx.value) {
+        for key in node.schema.keys() {
+            if !KEYWORDS.contains(&key.as_str()) && !key.starts_with("x-") {
+                findings.push(Finding::at(
+                    format!("{}/{}", node.path, escape(key)),
+                    format!("unknown JSON Schema keyword `{key}`"),
+                ));
+            }
+        }
+    }
+    findings
+}
+
+pub const MISSING_TYPE: Rule = Rule {
+    id: "AP012",
+    name: "schema-missing-type",
+    description: "`inputs`, `outputs` and each of their properties should declare a `type`.",
+    severity: Severity::Warning,
+    fixable: false,
+    check: missing_type,
+};
+
+fn missing_type(cx: &Context) -> Vec<Finding> {
+    nodes(cx.value)
+        .into_iter()
+        .filter(|n| n.named && !TYPE_LIKE.iter().any(|k| n.schema.contains_key(*k)))
+        .map(|n| Finding::at(n.path, "schema has no `type`"))
+        .collect()
+}
+
+pub const PROPERTY_DESCRIPTION: Rule = Rule {
+    id: "AP013",
+    name: "property-missing-description",
+    description: "Describe each property of `inputs`/`outputs`; agents choose arguments from these descriptions.",
+    severity: Severity::Warning,
+    fixable: false,
+    check: property_description,
+};
+
+fn property_description(cx: &Context) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for node in nodes(cx.value) {
+        let Some(Value::Object(props)) = node.schema.get("properties") else {
+            continue;
+        };
+        for (name, prop) in props {
+            let described = prop
+                .get("description")
+                .and_then(Value::as_str)
+                .is_some_and(|d| !d.trim().is_empty());
+            if prop.is_object() && !described {
+                findings.push(Finding::at(
+                    format!("{}/properties/{}", node.path, escape(name)),
+                    format!("property `{name}` has no `description`"),
+                ));
+            }
+        }
+    }
+    findings
+}
+
+pub const REQUIRED_UNDEFINED: Rule = Rule {
+    id: "AP014",
+    name: "required-not-defined",
+    description: "Every `required` name should be defined in `properties`.",
+    severity: Severity::Warning,
+    fixable: false,
+    check: required_undefined,
+};
+
+fn required_undefined(cx: &Context) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for node in nodes(cx.value) {
+        let Some(Value::Array(required)) = node.schema.get("required") else {
+            continue;
+        };
+        let props = node.schema.get("properties").and_then(Value::as_object);
+        for (index, name) in required.iter().enumerate() {
+            let Some(name) = name.as_str() else { continue };
+            if props.is_none_or(|p| !p.contains_key(name)) {
+                findings.push(Finding::at(
+                    format!("{}/required/{index}", node.path),
+                    format!("`{name}` is required but not defined in `properties`"),
+                ));
+            }
+        }
+    }
+    findings
+}
+
+fn io_schemas(manifest: &Value) -> impl Iterator<Item = (&'static str, &Value)> {
+    ["inputs", "outputs"]
+        .into_iter()
+        .filter_map(|key| manifest.get(key).map(|schema| (key, schema)))
+}
+
+/// A schema object somewhere inside `inputs` or `outputs`.
+struct Node<'a> {
+    /// JSON pointer from the manifest root
+    path: String,
+    schema: &'a Map<String, Value>,
+    /// The root itself or a named property, where a `type` is expected
+    named: bool,
+}
+
+/// Every schema object in `inputs` and `outputs`, roots first.
+fn nodes(manifest: &Value) -> Vec<Node<'_>> {
+    let mut out = Vec::new();
+    for (key, schema) in io_schemas(manifest) {
+        collect(schema, format!("/{key}"), true, &mut out);
+    }
+    out
+}
+
+fn collect<'a>(value: &'a Value, path: String, named: bool, out: &mut Vec<Node<'a>>) {
+    let Value::Object(schema) = value else {
+        return;
+    };
+    out.push(Node {
+        path: path.clone(),
+        schema,
+        named,
+    });
+    for (key, child) in schema {
+        let child_path = format!("{path}/{}", escape(key));
+        match key.as_str() {
+            "properties" => each_member(child, &child_path, true, out),
+            "patternProperties" | "$defs" | "definitions" | "dependentSchemas" => {
+                each_member(child, &child_path, false, out)
+            }
+            "allOf" | "anyOf" | "oneOf" | "prefixItems" => each_item(child, &child_path, out),
+            // draft-07 tuple form
+            "items" if child.is_array() => each_item(child, &child_path, out),
+            "items"
+            | "additionalProperties"
+            | "additionalItems"
+            | "not"
+            | "if"
+            | "then"
+            | "else"
+            | "contains"
+            | "propertyNames"
+            | "unevaluatedItems"
+            | "unevaluatedProperties"
+            | "contentSchema" => collect(child, child_path, false, out),
+            _ => {}
+        }
+    }
+}
+
+fn each_member<'a>(value: &'a Value, path: &str, named: bool, out: &mut Vec<Node<'a>>) {
+    if let Value::Object(members) = value {
+        for (name, child) in members {
+            collect(child, format!("{path}/{}", escape(name)), named, out);
+        }
+    }
+}
+
+fn each_item<'a>(value: &'a Value, path: &str, out: &mut Vec<Node<'a>>) {
+    if let Value::Array(items) = value {
+        for (index, child) in items.iter().enumerate() {
+            collect(child, format!("{path}/{index}"), false, out);
+        }
+    }
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{Catalog, tests::check_rule};
+    use serde_json::json;
+    use std::path::Path;
+
+    fn paths(rule: &Rule, manifest: Value) -> Vec<String> {
+        check_rule(
+            rule,
+            Path::new("agent.json"),
+            &manifest.to_string(),
+            &Catalog::default(),
+        )
+        .into_iter()
+        .map(|f| f.instance_path)
+        .collect()
+    }
+
+    fn tool(inputs: Value) -> Value {
+        json!({ "kind": "tool", "name": "t", "inputs": inputs, "outputs": { "type": "object" } })
+    }
+
+    #[test]
+    fn invalid_schemas_are_reported_where_they_break() {
+        let valid = tool(json!({ "type": "object", "properties": { "q": { "type": "string" } } }));
+        assert!(paths(&INVALID_IO_SCHEMA, valid).is_empty());
+        let nested = tool(json!({ "properties": { "q": { "type": "text" } } }));
+        assert_eq!(
+            paths(&INVALID_IO_SCHEMA, nested),
+            ["/inputs/properties/q/type"]
+        );
+        let pattern = tool(json!({ "type": "string", "pattern": "(" }));
+        assert_eq!(paths(&INVALID_IO_SCHEMA, pattern), ["/inputs"]);
+    }
+
+    #[test]
+    fn unknown_keywords_outside_x_extensions() {
+        let manifest = tool(json!({
+            "type": "object",
+            "x-ui": {},
+            "properties": { "a/b": { "type": "string", "minLenght": 1 } },
+            "$defs": { "d": { "requird": [] } },
+        }));
+        assert_eq!(
+            paths(&UNKNOWN_KEYWORD, manifest),
+            [
+                "/inputs/$defs/d/requird",
+                "/inputs/properties/a~1b/minLenght"
+            ]
+        );
+    }
+
+    #[test]
+    fn types_are_expected_on_roots_and_properties_only() {
+        let manifest = tool(json!({
+            "properties": {
+                "a": { "description": "no type" },
+                "b": { "$ref": "#/$defs/b" },
+                "c": { "type": "array", "items": { "minimum": 1 } },
+            },
+            "$defs": { "b": {} },
+        }));
+        assert_eq!(
+            paths(&MISSING_TYPE, manifest),
+            ["/inputs", "/inputs/properties/a"]
+        );
+    }
+
+    #[test]
+    fn properties_need_a_description() {
+        let manifest = tool(json!({
+            "type": "object",
+            "properties": {
+                "a": { "type": "string", "description": "documented" },
+                "b": { "type": "string", "description": "  " },
+                "c": true,
+                "d": { "type": "object", "properties": { "e": { "type": "string" } } },
+            },
+        }));
+        assert_eq!(
+            paths(&PROPERTY_DESCRIPTION, manifest),
+            [
+                "/inputs/properties/b",
+                "/inputs/properties/d",
+                "/inputs/properties/d/properties/e"
+            ]
+        );
+    }
+
+    #[test]
+    fn required_names_must_be_defined() {
+        let manifest = tool(json!({
+            "type": "object",
+            "required": ["a", "b"],
+            "properties": { "a": { "type": "string" } },
+            "allOf": [{ "required": ["c"] }],
+        }));
+        assert_eq!(
+            paths(&REQUIRED_UNDEFINED, manifest),
+            ["/inputs/required/1", "/inputs/allOf/0/required/0"]
+        );
+    }
+}
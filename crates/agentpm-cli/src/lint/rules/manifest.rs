@@ -0,0 +1,169 @@
+//! Rules about the manifest as a whole: schema conformance and top-level fields.
+
+use crate::lint::{Context, Finding, Fix, Rule, SCHEMA_URL, Severity};
+use crate::manifest::is_valid_name;
+use crate::permissions;
+use crate::util::json_edit;
+use serde_json::Value;
+
+pub const SCHEMA: Rule = Rule {
+    id: "AP000",
+    name: "schema",
+    description: "The manifest must match the agentpm manifest JSON Schema.",
+    severity: Severity::Error,
+    fixable: false,
+    check: schema,
+};
+
+fn schema(cx: &Context) -> Vec<Finding> {
+    match cx.schema.validate(cx.value) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|e| Finding {
+                message: e.to_string(),
+                instance_path: e.instance_path.to_string(),
+                schema_path: e.schema_path.to_string(),
+                fix: None,
+            })
+            .collect(),
+    }
+}
+
+pub const MISSING_SCHEMA: Rule = Rule {
+    id: "AP001",
+    name: "missing-schema",
+    description: "Set `$schema` so editors can offer completion and validation.",
+    severity: Severity::Warning,
+    fixable: true,
+    check: missing_schema,
+};
+
+fn missing_schema(cx: &Context) -> Vec<Finding> {
+    if !cx.value.is_object() || cx.value.get("$schema").is_some() {
+        return Vec::new();
+    }
+    let mut finding = Finding::at("", "Missing $schema; editors may lack IntelliSense.");
+    finding.fix = json_edit::push_member(cx.source, cx.spans, "$schema", &SCHEMA_URL)
+        .ok()
+        .map(|edit| Fix {
+            description: "add `$schema`".into(),
+            edits: vec![edit],
+        });
+    vec![finding]
+}
+
+pub const EMPTY_DESCRIPTION: Rule = Rule {
+    id: "AP002",
+    name: "empty-description",
+    description: "`description` is shown in search results and should say what the package does.",
+    severity: Severity::Warning,
+    fixable: false,
+    check: empty_description,
+};
+
+fn empty_description(cx: &Context) -> Vec<Finding> {
+    match cx.value.get("description") {
+        Some(Value::String(desc)) if desc.trim().is_empty() => vec![Finding::at(
+            "/description",
+            "`description` should not be empty",
+        )],
+        _ => Vec::new(),
+    }
+}
+
+pub const AGENT_PERMISSIONS: Rule = Rule {
+    id: "AP003",
+    name: "agent-permissions",
+    description: "Agents are never sandboxed themselves; `permissions` belongs on their tools.",
+    severity: Severity::Warning,
+    fixable: false,
+    check: agent_permissions,
+};
+
+fn agent_permissions(cx: &Context) -> Vec<Finding> {
+    let is_agent = cx.value.get("kind").and_then(Value::as_str) == Some("agent");
+    if !is_agent || cx.value.get("permissions").is_none() {
+        return Vec::new();
+    }
+    vec![Finding::at(
+        "/permissions",
+        "`permissions` only applies to tools; agents inherit them from their tools",
+    )]
+}
+
+pub const WILDCARD_PERMISSION: Rule = Rule {
+    id: "AP004",
+    name: "wildcard-permission",
+    description: "A `*` in `permissions.network` or `permissions.subprocess` defeats review.",
+    severity: Severity::Warning,
+    fixable: false,
+    check: wildcard_permission,
+};
+
+fn wildcard_permission(cx: &Context) -> Vec<Finding> {
+    let Some(perms) = cx.value.get("permissions") else {
+        return Vec::new();
+    };
+    let mut findings = Vec::new();
+    for (key, what) in [("network", "any host"), ("subprocess", "any program")] {
+        let wildcard = perms
+            .get(key)
+            .and_then(Value::as_array)
+            .and_then(|items| items.iter().position(|v| v == permissions::ANY));
+        if let Some(index) = wildcard {
+            findings.push(Finding::at(
+                format!("/permissions/{key}/{index}"),
+                format!("`permissions.{key}` allows {what}; list exactly what the tool needs"),
+            ));
+        }
+    }
+    findings
+}
+
+pub const NAME_FORMAT: Rule = Rule {
+    id: "AP005",
+    name: "name-format",
+    description: "`name` must be lowercase letters, digits and dashes, starting with a letter (at most 64 characters).",
+    severity: Severity::Warning,
+    fixable: true,
+    check: name_format,
+};
+
+fn name_format(cx: &Context) -> Vec<Finding> {
+    let Some(name) = cx.value.get("name").and_then(Value::as_str) else {
+        return Vec::new();
+    };
+    if is_valid_name(name) {
+        return Vec::new();
+    }
+    let mut finding = Finding::at(
+        "/name",
+        format!("`{name}` is not a valid name; use lowercase letters, digits and dashes"),
+    );
+    let normalized = normalize_name(name);
+    if is_valid_name(&normalized) {
+        finding.fix = cx
+            .spans
+            .pointer("/name")
+            .and_then(|target| json_edit::replace(cx.source, target, &normalized).ok())
+            .map(|edit| Fix {
+                description: format!("rename to `{normalized}`"),
+                edits: vec![edit],
+            });
+    }
+    vec![finding]
+}
+
+/// `My_Tool.v2` → `my-tool-v2`: lowercase, separators to dashes, anything else dropped.
+fn normalize_name(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        match c {
+            'a'..='z' | '0'..='9' => out.push(c),
+            'A'..='Z' => out.push(c.to_ascii_lowercase()),
+            '-' | '_' | '.' | ' ' if !out.ends_with('-') => out.push('-'),
+            _ => {}
+        }
+    }
+    out.trim_matches('-').to_string()
+}
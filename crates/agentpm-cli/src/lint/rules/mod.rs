@@ -0,0 +1,40 @@
+//! The rule registry. IDs are stable: never renumber or reuse one, since users
+//! reference them in config and CI.
+
+mod files;
+mod io_schema;
+mod manifest;
+mod tools;
+
+use super::Rule;
+
+pub static RULES: &[Rule] = &[
+    manifest::SCHEMA,
+    manifest::MISSING_SCHEMA,
+    manifest::EMPTY_DESCRIPTION,
+    manifest::AGENT_PERMISSIONS,
+    manifest::WILDCARD_PERMISSION,
+    manifest::NAME_FORMAT,
+    io_schema::INVALID_IO_SCHEMA,
+    io_schema::UNKNOWN_KEYWORD,
+    io_schema::MISSING_TYPE,
+    io_schema::PROPERTY_DESCRIPTION,
+    io_schema::REQUIRED_UNDEFINED,
+    files::ENTRYPOINT_MISSING,
+    files::ENTRYPOINT_OUTSIDE,
+    files::ENTRYPOINT_NOT_STARTABLE,
+    files::ENTRYPOINT_IGNORED,
+    tools::UNKNOWN_TOOL,
+    tools::DUPLICATE_TOOL,
+    tools::NO_MATCHING_VERSION,
+    tools::TOOL_CYCLE,
+    tools::UNSORTED_TOOLS,
+    tools::STRING_TOOL_REF,
+];
+
+/// Look a rule up by ID (`AP001`) or name (`missing-schema`), ignoring case.
+pub fn find(key: &str) -> Option<&'static Rule> {
+    RULES
+        .iter()
+        .find(|r| r.id.eq_ignore_ascii_case(key) || r.name.eq_ignore_ascii_case(key))
+}
@@ -0,0 +1,451 @@
+//! Rules about an agent's `tools`: each entry should resolve, against sibling manifests
+//! in the workspace, the agent's lockfile or the registry (see `lint::catalog`).
+
+use crate::lint::catalog::{Published, tool_refs};
+use crate::lint::{Context, Finding, Fix, Rule, Severity};
+use crate::manifest::ToolRef;
+use crate::util::json_edit::{self, TextEdit};
+use semver::Version;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const UNKNOWN_TOOL: Rule = Rule {
+    id: "AP030",
+    name: "unknown-tool",
+    description: "Each `tools` entry must name a workspace manifest, a locked tool or a published one.",
+    severity: Severity::Error,
+    fixable: false,
+    check: unknown_tool,
+};
+
+fn unknown_tool(cx: &Context) -> Vec<Finding> {
+    agent_tools(cx)
+        .into_iter()
+        .filter(|(_, tool)| {
+            let name = tool.name();
+            cx.catalog.local(name).is_empty()
+                && cx.catalog.lock_for(cx.file).is_none_or(|l| l.get(name).is_none())
+                // Only the registry can say a name doesn't exist
+                && matches!(cx.catalog.published(name), Some(Published::NotFound))
+        })
+        .map(|(index, tool)| {
+            Finding::at(
+                format!("/tools/{index}"),
+                format!(
+                    "`{}` is not in the workspace, agentpm.lock or the registry",
+                    tool.name()
+                ),
+            )
+        })
+        .collect()
+}
+
+pub const DUPLICATE_TOOL: Rule = Rule {
+    id: "AP031",
+    name: "duplicate-tool",
+    description: "A tool may appear only once in `tools`; one version of each is resolved.",
+    severity: Severity::Error,
+    fixable: true,
+    check: duplicate_tool,
+};
+
+fn duplicate_tool(cx: &Context) -> Vec<Finding> {
+    let mut seen = BTreeMap::new();
+    let mut findings = Vec::new();
+    for (index, tool) in agent_tools(cx) {
+        let Some(first) = seen.get(tool.name()) else {
+            seen.insert(tool.name().to_string(), tool);
+            continue;
+        };
+        let mut finding = Finding::at(
+            format!("/tools/{index}"),
+            format!("`{}` is listed more than once", tool.name()),
+        );
+        // Only an exact repeat is safe to drop; differing ranges need a decision
+        if first.range() == tool.range()
+            && let Some(tools) = cx.spans.pointer("/tools")
+        {
+            finding.fix = Some(Fix {
+                description: format!("remove the repeated `{}`", tool.name()),
+                edits: vec![json_edit::remove_item(tools, index)],
+            });
+        }
+        findings.push(finding);
+    }
+    findings
+}
+
+pub const NO_MATCHING_VERSION: Rule = Rule {
+    id: "AP032",
+    name: "no-matching-version",
+    description: "A `tools` version range must be valid semver and match at least one known version.",
+    severity: Severity::Error,
+    fixable: false,
+    check: no_matching_version,
+};
+
+fn no_matching_version(cx: &Context) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (index, tool) in agent_tools(cx) {
+        let name = tool.name();
+        let path = format!("/tools/{index}");
+        let req = match tool.version_req() {
+            Ok(req) => req,
+            Err(e) => {
+                findings.push(Finding::at(path, e.to_string()));
+                continue;
+            }
+        };
+
+        let mut known: Vec<Version> = cx
+            .catalog
+            .local(name)
+            .iter()
+            .filter_map(|m| m.version.clone())
+            .collect();
+        if let Some(Published::Versions(published)) = cx.catalog.published(name) {
+            known.extend(published.iter().cloned());
+        }
+        if let Some(locked) = cx.catalog.lock_for(cx.file).and_then(|l| l.get(name))
+            && let Ok(version) = Version::parse(&locked.version)
+        {
+            known.push(version);
+        }
+        if known.is_empty() || known.iter().any(|v| req.matches(v)) {
+            continue;
+        }
+        known.sort();
+        known.dedup();
+        let newest: Vec<String> = known.iter().rev().take(5).map(Version::to_string).collect();
+        findings.push(Finding::at(
+            path,
+            format!(
+                "no version of `{name}` matches `{}` (known: {}{})",
+                tool.range().unwrap_or("*"),
+                newest.join(", "),
+                if known.len() > newest.len() {
+                    ", ..."
+                } else {
+                    ""
+                }
+            ),
+        ));
+    }
+    findings
+}
+
+pub const TOOL_CYCLE: Rule = Rule {
+    id: "AP033",
+    name: "tool-cycle",
+    description: "An agent must not list itself in `tools`, directly or through workspace agents.",
+    severity: Severity::Error,
+    fixable: false,
+    check: tool_cycle,
+};
+
+fn tool_cycle(cx: &Context) -> Vec<Finding> {
+    let Some(own) = cx.value.get("name").and_then(Value::as_str) else {
+        return Vec::new();
+    };
+    let mut findings = Vec::new();
+    for (index, tool) in agent_tools(cx) {
+        let path = format!("/tools/{index}");
+        if tool.name() == own {
+            findings.push(Finding::at(
+                path,
+                format!("`{own}` lists itself in `tools`"),
+            ));
+        } else if let Some(chain) = path_back(cx, tool.name(), own) {
+            findings.push(Finding::at(
+                path,
+                format!("`{own}` → {} → `{own}` is a cycle", chain.join(" → ")),
+            ));
+        }
+    }
+    findings
+}
+
+pub const UNSORTED_TOOLS: Rule = Rule {
+    id: "AP034",
+    name: "unsorted-tools",
+    description: "Keep `tools` sorted by name so diffs and merges stay small.",
+    severity: Severity::Warning,
+    fixable: true,
+    check: unsorted_tools,
+};
+
+fn unsorted_tools(cx: &Context) -> Vec<Finding> {
+    let tools = agent_tools(cx);
+    // Entries that aren't tool refs are AP000's to report
+    let Some(Value::Array(all)) = cx.value.get("tools") else {
+        return Vec::new();
+    };
+    if tools.len() != all.len() || tools.is_sorted_by(|(_, a), (_, b)| a.name() <= b.name()) {
+        return Vec::new();
+    }
+    let mut finding = Finding::at("/tools", "`tools` is not sorted by name");
+    if let Some(array) = cx.spans.pointer("/tools") {
+        let items = array.items();
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        order.sort_by_key(|&i| tools[i].1.name());
+        // Move entries as written, so their own formatting survives
+        let edits = order
+            .iter()
+            .enumerate()
+            .filter(|(slot, from)| slot != *from)
+            .map(|(slot, &from)| TextEdit {
+                range: items[slot].span.clone(),
+                replacement: cx.source[items[from].span.clone()].to_string(),
+            })
+            .collect();
+        finding.fix = Some(Fix {
+            description: "sort `tools` by name".into(),
+            edits,
+        });
+    }
+    vec![finding]
+}
+
+pub const STRING_TOOL_REF: Rule = Rule {
+    id: "AP035",
+    name: "string-tool-ref",
+    description: "Prefer `{\"name\", \"version\"}` objects to `\"name@range\"` strings in `tools`.",
+    severity: Severity::Warning,
+    fixable: true,
+    check: string_tool_ref,
+};
+
+fn string_tool_ref(cx: &Context) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (index, tool) in agent_tools(cx) {
+        let ToolRef::Short(raw) = &tool else { continue };
+        let path = format!("/tools/{index}");
+        let object = match tool.range() {
+            Some(range) => json!({ "name": tool.name(), "version": range }),
+            None => json!({ "name": tool.name() }),
+        };
+        let mut finding = Finding::at(&path, format!("`\"{raw}\"` is a string tool reference"));
+        finding.fix = cx
+            .spans
+            .pointer(&path)
+            .and_then(|target| json_edit::replace(cx.source, target, &object).ok())
+            .map(|edit| Fix {
+                description: "convert to an object".into(),
+                edits: vec![edit],
+            });
+        findings.push(finding);
+    }
+    findings
+}
+
+/// Workspace names leading from `start` back to `target`, if any (depth-first).
+fn path_back(cx: &Context, start: &str, target: &str) -> Option<Vec<String>> {
+    let mut visited = BTreeSet::new();
+    let mut chain = vec![format!("`{start}`")];
+    walk(cx, start, target, &mut visited, &mut chain).then_some(chain)
+}
+
+fn walk(
+    cx: &Context,
+    name: &str,
+    target: &str,
+    visited: &mut BTreeSet<String>,
+    chain: &mut Vec<String>,
+) -> bool {
+    if !visited.insert(name.to_string()) {
+        return false;
+    }
+    for manifest in cx.catalog.local(name) {
+        for next in &manifest.tools {
+            if next == target {
+                return true;
+            }
+            chain.push(format!("`{next}`"));
+            if walk(cx, next, target, visited, chain) {
+                return true;
+            }
+            chain.pop();
+        }
+    }
+    false
+}
+
+fn agent_tools(cx: &Context) -> Vec<(usize, ToolRef)> {
+    if cx.value.get("kind").and_then(Value::as_str) != Some("agent") {
+        return Vec::new();
+    }
+    tool_refs(cx.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{Catalog, tests::check_rule};
+    use crate::lockfile::{LOCK_FILE, LockedTool, Lockfile};
+    use std::{fs, path::Path};
+
+    /// `ws/app` (the agent under test) next to `ws/summarize` 1.2.0 and `ws/planner`, an
+    /// agent listing `app`; `app` has `translate` 0.3.1 locked. The registry knows `search`
+    /// at 2.0.0 and 2.1.0 and has never heard of `ghost`.
+    fn workspace(app_tools: Value) -> (tempfile::TempDir, Catalog) {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, manifest: Value| {
+            let package = dir.path().join(name);
+            fs::create_dir_all(&package).unwrap();
+            fs::write(package.join("agent.json"), manifest.to_string()).unwrap();
+        };
+        write(
+            "app",
+            json!({ "kind": "agent", "name": "app", "tools": app_tools }),
+        );
+        write(
+            "summarize",
+            json!({ "kind": "tool", "name": "summarize", "version": "1.2.0" }),
+        );
+        write(
+            "planner",
+            json!({ "kind": "agent", "name": "planner", "tools": ["summarize", "app@*"] }),
+        );
+        Lockfile::new(vec![LockedTool {
+            name: "translate".into(),
+            version: "0.3.1".into(),
+            source: "https://registry.example".into(),
+            digest: "sha256:00".into(),
+            dependencies: Vec::new(),
+        }])
+        .write(&dir.path().join("app").join(LOCK_FILE))
+        .unwrap();
+
+        let mut catalog = Catalog::discover(&[dir.path().join("app/agent.json")]);
+        catalog.publish(
+            "search",
+            Published::Versions(vec![Version::new(2, 0, 0), Version::new(2, 1, 0)]),
+        );
+        catalog.publish("ghost", Published::NotFound);
+        catalog.publish("flaky", Published::Unreachable);
+        (dir, catalog)
+    }
+
+    fn findings(rule: &Rule, dir: &Path, catalog: &Catalog) -> Vec<(String, String)> {
+        let file = dir.join("app/agent.json");
+        let source = fs::read_to_string(&file).unwrap();
+        check_rule(rule, &file, &source, catalog)
+            .into_iter()
+            .map(|f| (f.instance_path, f.message))
+            .collect()
+    }
+
+    #[test]
+    fn unknown_tools_need_the_registry_to_say_so() {
+        let (dir, catalog) = workspace(json!([
+            "summarize",
+            "translate",
+            "search",
+            "ghost",
+            "flaky",
+            "unasked"
+        ]));
+        assert_eq!(
+            findings(&UNKNOWN_TOOL, dir.path(), &catalog),
+            [(
+                "/tools/3".into(),
+                "`ghost` is not in the workspace, agentpm.lock or the registry".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn versions_must_match_a_known_one() {
+        let (dir, catalog) = workspace(json!([
+            "summarize@^1",
+            "summarize@^2",
+            "translate@~0.3",
+            { "name": "translate", "version": "^0.4" },
+            "search@^3",
+            "search@not-a-range",
+            "unasked@^9",
+        ]));
+        let found = findings(&NO_MATCHING_VERSION, dir.path(), &catalog);
+        let paths: Vec<&str> = found.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, ["/tools/1", "/tools/3", "/tools/4", "/tools/5"]);
+        assert_eq!(
+            found[0].1,
+            "no version of `summarize` matches `^2` (known: 1.2.0)"
+        );
+        assert_eq!(
+            found[2].1,
+            "no version of `search` matches `^3` (known: 2.1.0, 2.0.0)"
+        );
+    }
+
+    #[test]
+    fn duplicates_are_dropped_only_when_identical() {
+        let (dir, catalog) = workspace(json!([
+            "search@^2",
+            "summarize",
+            "search@^2",
+            "summarize@^1"
+        ]));
+        let file = dir.path().join("app/agent.json");
+        let source = fs::read_to_string(&file).unwrap();
+        let found = check_rule(&DUPLICATE_TOOL, &file, &source, &catalog);
+        let paths: Vec<&str> = found.iter().map(|f| f.instance_path.as_str()).collect();
+        assert_eq!(paths, ["/tools/2", "/tools/3"]);
+        assert!(found[1].fix.is_none(), "differing ranges need a decision");
+
+        let fixed = json_edit::apply(&source, found[0].fix.clone().unwrap().edits);
+        let value: Value = serde_json::from_str(&fixed).unwrap();
+        assert_eq!(
+            value["tools"],
+            json!(["search@^2", "summarize", "summarize@^1"])
+        );
+    }
+
+    #[test]
+    fn cycles_through_workspace_agents() {
+        let (dir, catalog) = workspace(json!(["planner", "app", "summarize"]));
+        assert_eq!(
+            findings(&TOOL_CYCLE, dir.path(), &catalog),
+            [
+                (
+                    "/tools/0".into(),
+                    "`app` → `planner` → `app` is a cycle".into()
+                ),
+                ("/tools/1".into(), "`app` lists itself in `tools`".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn sorting_and_string_refs_fix_in_place() {
+        let (dir, catalog) = workspace(json!(["search@^2", { "name": "app" }, "summarize"]));
+        let file = dir.path().join("app/agent.json");
+        let source = fs::read_to_string(&file).unwrap();
+
+        let unsorted = check_rule(&UNSORTED_TOOLS, &file, &source, &catalog);
+        let sorted = json_edit::apply(&source, unsorted[0].fix.clone().unwrap().edits);
+        let value: Value = serde_json::from_str(&sorted).unwrap();
+        assert_eq!(
+            value["tools"],
+            json!([{ "name": "app" }, "search@^2", "summarize"])
+        );
+        assert!(check_rule(&UNSORTED_TOOLS, &file, &sorted, &catalog).is_empty());
+
+        let strings = check_rule(&STRING_TOOL_REF, &file, &sorted, &catalog);
+        let paths: Vec<&str> = strings.iter().map(|f| f.instance_path.as_str()).collect();
+        assert_eq!(paths, ["/tools/1", "/tools/2"]);
+        let edits = strings
+            .into_iter()
+            .flat_map(|f| f.fix.unwrap().edits)
+            .collect();
+        let value: Value = serde_json::from_str(&json_edit::apply(&sorted, edits)).unwrap();
+        assert_eq!(
+            value["tools"],
+            json!([
+                { "name": "app" },
+                { "name": "search", "version": "^2" },
+                { "name": "summarize" },
+            ])
+        );
+    }
+}
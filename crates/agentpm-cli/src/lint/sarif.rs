@@ -0,0 +1,213 @@
+//! SARIF 2.1.0 output, the format code-scanning tools ingest.
+
+use super::{LintFileReport, LintIssue, RULES, Severity};
+use crate::util::json_span;
+use serde_json::{Value, json};
+use std::ops::Range;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// One SARIF log with a single run: every rule, and every unresolved issue as a result.
+pub fn render(reports: &[LintFileReport]) -> Value {
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "name": rule.name,
+                "shortDescription": { "text": rule.description },
+                "defaultConfiguration": { "level": level(rule.severity) },
+                "properties": { "fixable": rule.fixable },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = reports
+        .iter()
+        .flat_map(|r| r.issues.iter().map(|i| result(r, i)))
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "agentpm",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/agentpm-dev/cli",
+                    "rules": rules,
+                }
+            },
+            // Our columns count characters, not UTF-16 units
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }]
+    })
+}
+
+fn result(report: &LintFileReport, issue: &LintIssue) -> Value {
+    let artifact = json!({ "uri": uri(&report.file) });
+    let mut location = json!({ "artifactLocation": artifact });
+    if let Some(span) = &issue.span {
+        location["region"] = region(&report.source, span);
+    }
+
+    let mut result = json!({
+        "level": level(issue.level),
+        "message": { "text": issue.message },
+        "locations": [{ "physicalLocation": location }],
+    });
+    if let Some(index) = RULES.iter().position(|r| r.id == issue.rule) {
+        result["ruleId"] = json!(issue.rule);
+        result["ruleIndex"] = json!(index);
+    }
+    if !issue.instance_path.is_empty() {
+        result["properties"] = json!({ "instancePath": issue.instance_path });
+    }
+    if let Some(fix) = &issue.fix {
+        let replacements: Vec<Value> = fix
+            .edits
+            .iter()
+            .map(|edit| {
+                json!({
+                    "deletedRegion": {
+                        "charOffset": report.source[..edit.range.start].chars().count(),
+                        "charLength": report.source[edit.range.clone()].chars().count(),
+                    },
+                    "insertedContent": { "text": edit.replacement },
+                })
+            })
+            .collect();
+        result["fixes"] = json!([{
+            "description": { "text": fix.description },
+            "artifactChanges": [{ "artifactLocation": artifact, "replacements": replacements }],
+        }]);
+    }
+    result
+}
+
+fn region(source: &str, span: &Range<usize>) -> Value {
+    let (start_line, start_column) = json_span::line_col(source, span.start);
+    let (end_line, end_column) = json_span::line_col(source, span.end);
+    json!({
+        "startLine": start_line,
+        "startColumn": start_column,
+        "endLine": end_line,
+        "endColumn": end_column,
+    })
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+/// Relative paths stay relative (with `/` separators) so results map onto the checkout.
+fn uri(file: &str) -> String {
+    let path = file.replace('\\', "/");
+    let path = path.trim_start_matches("./");
+    if path.starts_with('/') {
+        format!("file://{path}")
+    } else {
+        path.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::Fix;
+    use crate::util::json_edit::TextEdit;
+
+    fn issue(rule: &'static str, level: Severity, span: Option<Range<usize>>) -> LintIssue {
+        LintIssue {
+            file: String::new(),
+            rule,
+            level,
+            message: format!("{rule} message"),
+            instance_path: String::new(),
+            schema_path: String::new(),
+            line: None,
+            column: None,
+            fix: None,
+            span,
+        }
+    }
+
+    #[test]
+    fn renders_rules_results_and_fixes() {
+        let source = "{\"description\": \"café\", \"name\": \"Demo\"}";
+        let name = source.find("\"Demo\"").unwrap();
+        let mut fixable = issue("AP005", Severity::Warning, Some(name..name + 6));
+        fixable.instance_path = "/name".into();
+        fixable.fix = Some(Fix {
+            description: "lowercase the name".into(),
+            edits: vec![TextEdit {
+                range: name + 1..name + 2,
+                replacement: "d".into(),
+            }],
+        });
+        let reports = [
+            LintFileReport {
+                file: "./demo/agent.json".into(),
+                ok: true,
+                issues: vec![fixable],
+                fixed: 0,
+                source: source.into(),
+                diff: None,
+            },
+            LintFileReport {
+                file: "/abs/agent.json".into(),
+                ok: false,
+                issues: vec![issue("", Severity::Error, None)],
+                fixed: 0,
+                source: String::new(),
+                diff: None,
+            },
+        ];
+
+        let log = render(&reports);
+        let run = &log["runs"][0];
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"].as_array().unwrap().len(),
+            RULES.len()
+        );
+        let index = RULES.iter().position(|r| r.id == "AP005").unwrap();
+        assert_eq!(run["tool"]["driver"]["rules"][index]["name"], "name-format");
+
+        let first = &run["results"][0];
+        assert_eq!(first["ruleId"], "AP005");
+        assert_eq!(first["ruleIndex"], index);
+        assert_eq!(first["level"], "warning");
+        assert_eq!(first["properties"]["instancePath"], "/name");
+        let location = &first["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "demo/agent.json");
+        // Columns and offsets count characters, so `é` is one
+        let column = source[..name].chars().count() + 1;
+        assert_eq!(
+            location["region"],
+            json!({ "startLine": 1, "startColumn": column, "endLine": 1, "endColumn": column + 6 })
+        );
+        let change = &first["fixes"][0]["artifactChanges"][0];
+        assert_eq!(
+            change["replacements"][0],
+            json!({
+                "deletedRegion": { "charOffset": column, "charLength": 1 },
+                "insertedContent": { "text": "d" },
+            })
+        );
+
+        let second = &run["results"][1];
+        assert_eq!(second["level"], "error");
+        assert!(second.get("ruleId").is_none() && second.get("fixes").is_none());
+        let location = &second["locations"][0]["physicalLocation"];
+        assert_eq!(
+            location["artifactLocation"]["uri"],
+            "file:///abs/agent.json"
+        );
+        assert!(location.get("region").is_none());
+    }
+}
@@ -0,0 +1,252 @@
+//! Schemas fetched over HTTP, cached under the user cache dir so `lint` keeps working
+//! offline. Each URL gets `<sha256(url)>.json` plus a `.meta.json` holding the validators
+//! (`ETag`, `Last-Modified`) used to revalidate it.
+
+use crate::assets::MANIFEST_SCHEMA;
+use crate::io::fs::write_atomic;
+use crate::lint::SCHEMA_URL;
+use crate::prelude::*;
+use anyhow::{anyhow, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{fs, path::PathBuf, time::Duration};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Meta {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+}
+
+pub struct SchemaCache {
+    root: PathBuf,
+    /// Never touch the network; use the cache or the embedded schema
+    offline: bool,
+}
+
+impl SchemaCache {
+    pub fn new(cfg: &Config, offline: bool) -> Self {
+        Self {
+            root: cfg.cache_dir.join("schemas"),
+            offline,
+        }
+    }
+
+    /// The schema at `url`: revalidated from the cache when possible, else fetched. When
+    /// the network fails, fall back to the cached copy, then (for the manifest schema)
+    /// the one compiled into this binary.
+    pub fn load(&self, url: &str) -> Result<Value> {
+        let cached = self.cached(url);
+        if self.offline {
+            return match cached {
+                Some((value, _)) => Ok(value),
+                None => embedded(url).ok_or_else(|| {
+                    anyhow!("schema {url} is not cached; run once without --offline to fetch it")
+                }),
+            };
+        }
+
+        match (
+            self.fetch(url, cached.as_ref().map(|(_, meta)| meta)),
+            cached,
+        ) {
+            // A 304 means the cached copy is current
+            (Ok(Some(value)), _) | (Ok(None), Some((value, _))) => Ok(value),
+            (Ok(None), None) => unreachable!("fetch only reports 304 for a cached copy"),
+            (Err(e), cached) => {
+                debug!("fetching schema {url}: {e:#}");
+                if let Some((value, _)) = cached {
+                    eprintln!("note: could not fetch {url}; using the cached copy");
+                    Ok(value)
+                } else if let Some(value) = embedded(url) {
+                    eprintln!("note: could not fetch {url}; using the schema built into agentpm");
+                    Ok(value)
+                } else {
+                    Err(e.context(format!("fetching schema {url}")))
+                }
+            }
+        }
+    }
+
+    /// GET `url`, conditional on `meta`'s validators. `None` means not modified.
+    fn fetch(&self, url: &str, meta: Option<&Meta>) -> Result<Option<Value>> {
+        let mut request = ureq::get(url).timeout(TIMEOUT);
+        if let Some(meta) = meta {
+            if let Some(etag) = &meta.etag {
+                request = request.set("If-None-Match", etag);
+            }
+            if let Some(date) = &meta.last_modified {
+                request = request.set("If-Modified-Since", date);
+            }
+        }
+        let response = request.call()?;
+        if response.status() == 304 {
+            if meta.is_none() {
+                bail!("unexpected 304 Not Modified");
+            }
+            return Ok(None);
+        }
+
+        let fresh = Meta {
+            url: url.to_string(),
+            etag: response.header("ETag").map(str::to_string),
+            last_modified: response.header("Last-Modified").map(str::to_string),
+        };
+        let body = response.into_string()?;
+        let value: Value = serde_json::from_str(&body).context("Invalid schema JSON")?;
+
+        // A cache we can't write only costs a refetch next time
+        let (body_path, meta_path) = self.paths(url);
+        if let Err(e) = write_atomic(&body_path, &body)
+            .and_then(|()| write_atomic(&meta_path, &serde_json::to_string_pretty(&fresh)?))
+        {
+            debug!("caching schema {url}: {e:#}");
+        }
+        Ok(Some(value))
+    }
+
+    fn cached(&self, url: &str) -> Option<(Value, Meta)> {
+        let (body_path, meta_path) = self.paths(url);
+        let value = serde_json::from_str(&fs::read_to_string(body_path).ok()?).ok()?;
+        let meta = fs::read_to_string(meta_path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Some((value, meta))
+    }
+
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = hex::encode(Sha256::digest(url.as_bytes()));
+        (
+            self.root.join(format!("{key}.json")),
+            self.root.join(format!("{key}.meta.json")),
+        )
+    }
+}
+
+/// The manifest schema this binary was built with, if `url` is where it's published.
+fn embedded(url: &str) -> Option<Value> {
+    (url == SCHEMA_URL)
+        .then(|| serde_json::from_str(MANIFEST_SCHEMA).expect("embedded schema is valid JSON"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    /// Serves `{"version": 1}` with `ETag: "v1"`, answering 304 to requests that send it
+    /// back. Returns the URL and the request lines (with any `If-None-Match`) seen so far.
+    fn server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/schema.json", listener.local_addr().unwrap());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end().to_string();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if !line.contains(':') || line.starts_with("If-None-Match") {
+                        request.push(line);
+                    }
+                }
+                let revalidated = request.iter().any(|l| l == "If-None-Match: \"v1\"");
+                log.lock().unwrap().push(request.join(" | "));
+                let response = if revalidated {
+                    "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nContent-Length: 0\r\n\r\n"
+                        .to_string()
+                } else {
+                    let body = r#"{"version": 1}"#;
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\n\r\n{body}",
+                        body.len()
+                    )
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, seen)
+    }
+
+    fn cache(root: &std::path::Path, offline: bool) -> SchemaCache {
+        SchemaCache {
+            root: root.to_path_buf(),
+            offline,
+        }
+    }
+
+    /// A URL nothing listens on.
+    fn dead_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/schema.json", listener.local_addr().unwrap())
+    }
+
+    #[test]
+    fn revalidates_the_cached_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, seen) = server();
+        let online = cache(dir.path(), false);
+
+        assert_eq!(online.load(&url).unwrap(), json!({ "version": 1 }));
+        let (_, meta) = online.cached(&url).unwrap();
+        assert_eq!(meta.etag.as_deref(), Some("\"v1\""));
+
+        // The second load sends the ETag back and keeps the cached body on 304
+        assert_eq!(online.load(&url).unwrap(), json!({ "version": 1 }));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                "GET /schema.json HTTP/1.1",
+                "GET /schema.json HTTP/1.1 | If-None-Match: \"v1\""
+            ]
+        );
+
+        // Offline, the cache answers without a request
+        assert_eq!(
+            cache(dir.path(), true).load(&url).unwrap(),
+            json!({ "version": 1 })
+        );
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn falls_back_to_the_cache_then_the_embedded_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = dead_url();
+        let err = cache(dir.path(), false).load(&url).unwrap_err();
+        assert!(format!("{err:#}").contains("fetching schema"), "{err:#}");
+        let err = cache(dir.path(), true).load(&url).unwrap_err();
+        assert!(err.to_string().contains("is not cached"), "{err}");
+
+        // A copy cached earlier stands in for an unreachable server
+        let (body, _) = cache(dir.path(), false).paths(&url);
+        write_atomic(&body, r#"{"cached": true}"#).unwrap();
+        assert_eq!(
+            cache(dir.path(), false).load(&url).unwrap(),
+            json!({ "cached": true })
+        );
+
+        // The manifest schema needs no network at all
+        let embedded: Value = serde_json::from_str(MANIFEST_SCHEMA).unwrap();
+        assert_eq!(cache(dir.path(), true).load(SCHEMA_URL).unwrap(), embedded);
+    }
+}
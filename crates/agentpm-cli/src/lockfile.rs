@@ -0,0 +1,147 @@
+use crate::io::fs::write_atomic;
+use crate::manifest::{ToolRef, check_name, parse_range};
+use crate::prelude::*;
+use anyhow::bail;
+use semver::VersionReq;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+pub const LOCK_FILE: &str = "agentpm.lock";
+const LOCK_VERSION: u32 = 1;
+const LOCK_HEADER: &str = "# This file is generated by agentpm. Do not edit it by hand.\n\n";
+
+/// agentpm.lock: the exact tool versions an agent resolved to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub version: u32,
+    /// Sorted by name; one entry per tool (resolution is flat)
+    #[serde(default, rename = "tool")]
+    pub tools: Vec<LockedTool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedTool {
+    pub name: String,
+    pub version: String,
+    /// Registry the tool was resolved from
+    pub source: String,
+    /// Archive digest, e.g. "sha256:<hex>"
+    pub digest: String,
+    /// Requirements of this tool, as "name" or "name@range"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+}
+
+/// One line of "what changed" between two lockfiles.
+#[derive(Debug, Clone)]
+pub enum LockChange<'a> {
+    Added(&'a LockedTool),
+    Updated {
+        from: &'a LockedTool,
+        to: &'a LockedTool,
+    },
+    Removed(&'a LockedTool),
+}
+
+impl Lockfile {
+    pub fn new(mut tools: Vec<LockedTool>) -> Self {
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            version: LOCK_VERSION,
+            tools,
+        }
+    }
+
+    /// Returns `None` when there is no lockfile yet.
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let lock: Lockfile =
+            toml::from_str(&text).with_context(|| format!("parsing {}", path.display()))?;
+        if lock.version > LOCK_VERSION {
+            bail!(
+                "{} uses lockfile version {}, but this agentpm only understands up to {}. Upgrade agentpm.",
+                path.display(),
+                lock.version,
+                LOCK_VERSION
+            );
+        }
+        for tool in &lock.tools {
+            check_name(&tool.name).with_context(|| format!("in {}", path.display()))?;
+        }
+        Ok(Some(lock))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let body = toml::to_string(self).context("serializing lockfile")?;
+        write_atomic(path, &format!("{LOCK_HEADER}{body}"))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LockedTool> {
+        self.tools.iter().find(|t| t.name == name)
+    }
+
+    /// Changes needed to go from `old` (possibly absent) to `self`.
+    pub fn changes_from<'a>(&'a self, old: Option<&'a Lockfile>) -> Vec<LockChange<'a>> {
+        let before: BTreeMap<&str, &LockedTool> = old
+            .map(|l| l.tools.iter().map(|t| (t.name.as_str(), t)).collect())
+            .unwrap_or_default();
+        let after: BTreeMap<&str, &LockedTool> =
+            self.tools.iter().map(|t| (t.name.as_str(), t)).collect();
+
+        let mut out = Vec::new();
+        for (name, to) in &after {
+            match before.get(name) {
+                None => out.push(LockChange::Added(to)),
+                Some(from) if from.version != to.version || from.digest != to.digest => {
+                    out.push(LockChange::Updated { from, to })
+                }
+                Some(_) => {}
+            }
+        }
+        for (name, from) in &before {
+            if !after.contains_key(name) {
+                out.push(LockChange::Removed(from));
+            }
+        }
+        out
+    }
+}
+
+impl LockedTool {
+    /// Parsed `dependencies` as (name, range) pairs.
+    pub fn requirements(&self) -> Result<Vec<(String, VersionReq)>> {
+        self.dependencies
+            .iter()
+            .map(|dep| {
+                let dep = ToolRef::Short(dep.clone());
+                Ok((
+                    dep.name().to_string(),
+                    parse_range(dep.name(), dep.range())?,
+                ))
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for LockChange<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LockChange::Added(t) => write!(f, "Adding {} v{}", t.name, t.version),
+            LockChange::Updated { from, to } if from.version == to.version => {
+                write!(f, "Updating {} v{} (new digest)", to.name, to.version)
+            }
+            LockChange::Updated { from, to } => {
+                write!(
+                    f,
+                    "Updating {} v{} -> v{}",
+                    to.name, from.version, to.version
+                )
+            }
+            LockChange::Removed(t) => write!(f, "Removing {} v{}", t.name, t.version),
+        }
+    }
+}
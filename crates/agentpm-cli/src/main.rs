@@ -6,8 +6,20 @@ mod assets;
 mod auth;
 mod commands;
 mod config;
+mod env;
+mod fuzz;
+mod history;
 mod io;
+mod lint;
+mod lockfile;
+mod manifest;
+mod permissions;
 mod prelude;
+mod project;
+mod resolve;
+mod runner;
+mod store;
+mod suite;
 mod util;
 /*
 TODO:
@@ -47,6 +59,20 @@ async fn main() -> Result<()> {
         commands::Commands::Whoami(args) => args.run(cli.base_url.clone()).await,
         commands::Commands::Login(args) => args.run(cli.base_url.clone()).await,
         commands::Commands::Init(args) => args.run(cli.base_url.clone()).await,
-        commands::Commands::Lint(args) => args.run().await,
+        commands::Commands::Lint(args) => args.run(cli.base_url.clone()).await,
+        commands::Commands::Lock(args) => args.run(cli.base_url.clone()).await,
+        commands::Commands::Install(args) => args.run(cli.base_url.clone()).await,
+        commands::Commands::Add(args) => args.run(cli.base_url.clone()).await,
+        commands::Commands::Remove(args) => args.run(cli.base_url.clone()).await,
+        commands::Commands::Outdated(args) => args.run(cli.base_url.clone()).await,
+        commands::Commands::Update(args) => args.run(cli.base_url.clone()).await,
+        commands::Commands::Tree(args) => args.run().await,
+        commands::Commands::Info(args) => args.run().await,
+        commands::Commands::Test(args) => args.run().await,
+        commands::Commands::Fuzz(args) => args.run().await,
+        commands::Commands::Dev(args) => args.run(cli.base_url.clone()).await,
+        commands::Commands::Run(args) => args.run(cli.base_url.clone()).await,
+        commands::Commands::History(args) => args.run(cli.base_url.clone()).await,
+        commands::Commands::Replay(args) => args.run(cli.base_url.clone()).await,
     }
 }
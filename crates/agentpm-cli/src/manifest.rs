@@ -0,0 +1,179 @@
+use crate::env::EnvSpec;
+use crate::permissions::Permissions;
+use crate::prelude::*;
+use crate::runner::runtime::Runtime;
+use crate::util::json_edit::{self, TextEdit};
+use crate::util::json_span::{self, Node, Spanned};
+use anyhow::bail;
+use semver::VersionReq;
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::Path};
+
+pub const MANIFEST_FILE: &str = "agent.json";
+
+/// Typed view of agent.json. Lint works on the raw JSON; everything else uses this.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    pub kind: ManifestKind,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tools: Vec<ToolRef>,
+    pub entrypoint: Option<String>,
+    /// How to execute `entrypoint`; detected when omitted
+    pub runtime: Option<Runtime>,
+    pub inputs: Option<Value>,
+    pub outputs: Option<Value>,
+    #[serde(default)]
+    pub permissions: Permissions,
+    /// Environment variables the tool reads, by name
+    #[serde(default)]
+    pub env: BTreeMap<String, EnvSpec>,
+    /// Golden test cases, validated by `agentpm test`
+    #[serde(default)]
+    pub examples: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestKind {
+    Agent,
+    Tool,
+}
+
+/// A `tools` entry: either "name", "name@range" or { "name": ..., "version": ... }
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ToolRef {
+    Short(String),
+    Detailed {
+        name: String,
+        version: Option<String>,
+    },
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&raw).with_context(|| format!("Invalid manifest {}", path.display()))
+    }
+}
+
+impl ToolRef {
+    pub fn name(&self) -> &str {
+        match self {
+            ToolRef::Short(s) => s.split_once('@').map_or(s.as_str(), |(name, _)| name),
+            ToolRef::Detailed { name, .. } => name,
+        }
+    }
+
+    /// The declared range as written (`None` when unconstrained)
+    pub fn range(&self) -> Option<&str> {
+        match self {
+            ToolRef::Short(s) => s.split_once('@').map(|(_, range)| range),
+            ToolRef::Detailed { version, .. } => version.as_deref(),
+        }
+    }
+
+    pub fn version_req(&self) -> Result<VersionReq> {
+        parse_range(self.name(), self.range())
+    }
+}
+
+impl std::fmt::Display for ToolRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.range() {
+            Some(range) => write!(f, "{}@{}", self.name(), range),
+            None => f.write_str(self.name()),
+        }
+    }
+}
+
+/// Whether `name` is a valid package name (`^[a-z][a-z0-9-]{0,63}$`, as the schema says).
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && name.len() <= 64
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Reject anything but a package name. Tool names become directory names under
+/// `.agentpm/tools`, so one like `../x` from a lockfile or the registry must never get there.
+pub fn check_name(name: &str) -> Result<()> {
+    if !is_valid_name(name) {
+        bail!("{name:?} is not a valid tool name (lowercase letters, digits and dashes)");
+    }
+    Ok(())
+}
+
+/// Parse a semver range; missing or empty means any version.
+pub fn parse_range(name: &str, range: Option<&str>) -> Result<VersionReq> {
+    match range.map(str::trim) {
+        None | Some("") => Ok(VersionReq::STAR),
+        Some(r) => VersionReq::parse(r)
+            .with_context(|| format!("Invalid version range `{r}` for tool `{name}`")),
+    }
+}
+
+/// Add `name@range` to the `tools` array of agent.json source text, or change the range of an
+/// existing entry, touching nothing else. New entries use the object form.
+pub fn set_tool(text: &str, name: &str, range: &str) -> Result<String> {
+    let doc = json_span::parse(text)?;
+    let entry = serde_json::json!({ "name": name, "version": range });
+    let edit = match doc.get("tools") {
+        None => json_edit::push_member(text, &doc, "tools", &[entry])?,
+        Some(tools) => {
+            if !matches!(tools.node, Node::Array(_)) {
+                bail!("`tools` must be an array");
+            }
+            match tools.items().iter().find(|t| tool_name(t) == Some(name)) {
+                None => json_edit::push_item(text, tools, &entry)?,
+                Some(existing) => set_range(text, existing, name, range)?,
+            }
+        }
+    };
+    Ok(json_edit::apply(text, vec![edit]))
+}
+
+/// Remove every `tools` entry named `name`; `None` if there was none.
+pub fn remove_tool(text: &str, name: &str) -> Result<Option<String>> {
+    let doc = json_span::parse(text)?;
+    let Some(tools) = doc.get("tools") else {
+        return Ok(None);
+    };
+    // Remove from the back so earlier spans stay valid
+    let mut out = text.to_string();
+    let mut removed = false;
+    for index in (0..tools.items().len()).rev() {
+        if tool_name(&tools.items()[index]) == Some(name) {
+            let doc = json_span::parse(&out)?;
+            let tools = doc.get("tools").expect("tools still present");
+            out = json_edit::apply(&out, vec![json_edit::remove_item(tools, index)]);
+            removed = true;
+        }
+    }
+    Ok(removed.then_some(out))
+}
+
+/// Name of a `tools` entry in either form.
+pub fn tool_name(entry: &Spanned) -> Option<&str> {
+    match &entry.node {
+        Node::String(s) => Some(s.split_once('@').map_or(s.as_str(), |(name, _)| name)),
+        Node::Object(_) => entry.get("name").and_then(Spanned::as_str),
+        _ => None,
+    }
+}
+
+fn set_range(text: &str, entry: &Spanned, name: &str, range: &str) -> Result<TextEdit> {
+    if entry.as_str().is_some() {
+        return json_edit::replace(text, entry, &format!("{name}@{range}"));
+    }
+    match entry.get("version") {
+        Some(version) => json_edit::replace(text, version, &range),
+        None => json_edit::push_member(text, entry, "version", &range),
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Wildcard entry for `network` and `subprocess`.
+pub const ANY: &str = "*";
+
+/// The sandbox cuts the network with a namespace, which is all or nothing: it can't keep a
+/// tool to the hosts it lists.
+pub const NETWORK_NOTE: &str = "any `network` permission allows every host; `run --sandbox` does not limit it to the listed ones";
+
+/// What a tool needs from the machine it runs on, from the `permissions` section of agent.json.
+/// Shown by `info`, compared by `install` across upgrades, enforced by `run --sandbox`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Permissions {
+    /// Hosts the tool talks to (`*` for any)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub network: Vec<String>,
+    /// Paths outside the package the tool reads or writes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filesystem: Vec<FsPermission>,
+    /// Environment variables the tool reads
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+    /// Programs the tool starts (`*` for any)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subprocess: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FsPermission {
+    /// Absolute, or relative to the user's home with `~/`
+    pub path: String,
+    #[serde(default)]
+    pub access: FsAccess,
+}
+
+/// `write` implies `read`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FsAccess {
+    #[default]
+    Read,
+    Write,
+}
+
+impl Permissions {
+    pub fn is_empty(&self) -> bool {
+        self.network.is_empty()
+            && self.filesystem.is_empty()
+            && self.env.is_empty()
+            && self.subprocess.is_empty()
+    }
+
+    /// Whether the tool may use the network at all. Hosts are informational: see `NETWORK_NOTE`.
+    pub fn any_network(&self) -> bool {
+        !self.network.is_empty()
+    }
+
+    pub fn any_subprocess(&self) -> bool {
+        self.subprocess.iter().any(|p| p == ANY)
+    }
+
+    /// One human-readable line per grant, e.g. `network: api.example.com`.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        lines.extend(self.network.iter().map(|h| format!("network: {h}")));
+        lines.extend(self.filesystem.iter().map(FsPermission::line));
+        lines.extend(self.env.iter().map(|v| format!("env: {v}")));
+        lines.extend(self.subprocess.iter().map(|p| format!("subprocess: {p}")));
+        lines
+    }
+
+    /// Grants in `self` that `old` did not already cover, as `lines()`.
+    pub fn grown_from(&self, old: &Permissions) -> Vec<String> {
+        let covered = |list: &[String], item: &String| list.iter().any(|o| o == item || o == ANY);
+        let mut grown = Vec::new();
+        for host in &self.network {
+            if !covered(&old.network, host) {
+                grown.push(format!("network: {host}"));
+            }
+        }
+        for fs in &self.filesystem {
+            if !old
+                .filesystem
+                .iter()
+                .any(|o| o.path == fs.path && o.access >= fs.access)
+            {
+                grown.push(fs.line());
+            }
+        }
+        for var in &self.env {
+            if !old.env.contains(var) {
+                grown.push(format!("env: {var}"));
+            }
+        }
+        for program in &self.subprocess {
+            if !covered(&old.subprocess, program) {
+                grown.push(format!("subprocess: {program}"));
+            }
+        }
+        grown
+    }
+}
+
+impl FsPermission {
+    fn line(&self) -> String {
+        let access = match self.access {
+            FsAccess::Read => "read",
+            FsAccess::Write => "read/write",
+        };
+        format!("filesystem: {} ({access})", self.path)
+    }
+
+    /// The declared path with `~` expanded; `None` if there is no home directory to expand to.
+    pub fn resolve(&self) -> Option<PathBuf> {
+        match self.path.strip_prefix('~') {
+            Some(rest) => {
+                let home = directories::BaseDirs::new()?.home_dir().to_path_buf();
+                Some(home.join(rest.trim_start_matches('/')))
+            }
+            None => Some(PathBuf::from(&self.path)),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::lockfile::LOCK_FILE;
+use crate::manifest::{MANIFEST_FILE, Manifest, check_name};
+use crate::prelude::*;
+use anyhow::anyhow;
+use std::path::PathBuf;
+
+/// The agent.json a command operates on, plus the directory that holds it.
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub root: PathBuf,
+    pub manifest: Manifest,
+}
+
+impl Project {
+    /// Find agent.json in the current directory or the nearest parent.
+    pub fn discover() -> Result<Self> {
+        let cwd = std::env::current_dir()?;
+        let root = cwd
+            .ancestors()
+            .find(|dir| dir.join(MANIFEST_FILE).is_file())
+            .ok_or_else(|| {
+                anyhow!(
+                    "No {MANIFEST_FILE} found in {} or any parent directory. Try: `agentpm init`.",
+                    cwd.display()
+                )
+            })?
+            .to_path_buf();
+        Self::at(root)
+    }
+
+    /// The project whose agent.json is in `root`.
+    pub fn at(root: PathBuf) -> Result<Self> {
+        let manifest = Manifest::load(&root.join(MANIFEST_FILE))?;
+        Ok(Self { root, manifest })
+    }
+
+    pub fn manifest_path(&self) -> PathBuf {
+        self.root.join(MANIFEST_FILE)
+    }
+
+    pub fn lock_path(&self) -> PathBuf {
+        self.root.join(LOCK_FILE)
+    }
+
+    /// Project-local install root: `.agentpm/`
+    pub fn local_dir(&self) -> PathBuf {
+        self.root.join(".agentpm")
+    }
+
+    /// Installed packages, one directory per tool name
+    pub fn tools_dir(&self) -> PathBuf {
+        self.local_dir().join("tools")
+    }
+
+    /// Where the tool `name` is installed; errors for anything but a package name.
+    pub fn tool_dir(&self, name: &str) -> Result<PathBuf> {
+        check_name(name)?;
+        Ok(self.tools_dir().join(name))
+    }
+
+    /// Copy of the lockfile as of the last `agentpm install`
+    pub fn installed_lock_path(&self) -> PathBuf {
+        self.local_dir().join("installed.lock")
+    }
+}
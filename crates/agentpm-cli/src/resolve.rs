@@ -0,0 +1,344 @@
+use crate::lockfile::{LockedTool, Lockfile};
+use crate::manifest::{Manifest, check_name};
+use crate::prelude::*;
+use agentpm_sdk::ToolVersion;
+use anyhow::bail;
+use semver::{Version, VersionReq};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Resolution settles in a couple of rounds; this only guards against flip-flopping.
+const MAX_ROUNDS: usize = 32;
+
+#[derive(Debug, Default, Clone)]
+pub struct ResolveOptions {
+    /// Which tools may move off their locked version
+    pub update: Update,
+    /// Never contact the registry; every tool must already be locked
+    pub offline: bool,
+}
+
+/// Tools whose locked version is ignored in favour of the newest matching one.
+#[derive(Debug, Default, Clone)]
+pub enum Update {
+    #[default]
+    Nothing,
+    All,
+    Only(Vec<String>),
+}
+
+impl Update {
+    fn applies_to(&self, name: &str) -> bool {
+        match self {
+            Update::Nothing => false,
+            Update::All => true,
+            Update::Only(names) => names.iter().any(|n| n == name),
+        }
+    }
+}
+
+/// A range some package placed on a tool.
+#[derive(Debug, Clone)]
+struct Requirement {
+    req: VersionReq,
+    from: String,
+}
+
+/// Flat resolver: one version per tool name, highest version satisfying every range,
+/// preferring what the previous lockfile pinned.
+pub struct Resolver<'a> {
+    client: &'a AgentPmClient,
+    previous: Option<&'a Lockfile>,
+    opts: ResolveOptions,
+    versions: HashMap<String, Vec<ToolVersion>>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(
+        client: &'a AgentPmClient,
+        previous: Option<&'a Lockfile>,
+        opts: ResolveOptions,
+    ) -> Self {
+        Self {
+            client,
+            previous,
+            opts,
+            versions: HashMap::new(),
+        }
+    }
+
+    pub async fn resolve(&mut self, manifest: &Manifest) -> Result<Lockfile> {
+        let mut roots = Vec::new();
+        for tool in &manifest.tools {
+            roots.push((tool.name().to_string(), tool.version_req()?));
+        }
+
+        let mut picks: BTreeMap<String, LockedTool> = BTreeMap::new();
+        for _ in 0..MAX_ROUNDS {
+            let reqs = collect_requirements(&manifest.name, &roots, &picks)?;
+
+            let mut next = BTreeMap::new();
+            for (name, reqs) in &reqs {
+                let pick = match picks.get(name) {
+                    Some(current) if satisfies_all(current, reqs) => current.clone(),
+                    _ => self.pick(name, reqs).await?,
+                };
+                next.insert(name.clone(), pick);
+            }
+
+            if next == picks {
+                return Ok(Lockfile::new(picks.into_values().collect()));
+            }
+            picks = next;
+        }
+        bail!("Dependency resolution did not settle after {MAX_ROUNDS} rounds")
+    }
+
+    /// Every published version of `name` (cached per run).
+    pub async fn versions(&mut self, name: &str) -> Result<&[ToolVersion]> {
+        check_name(name)?;
+        if !self.versions.contains_key(name) {
+            let versions = match self.client.tool_versions(name).await {
+                Ok(v) => v,
+                Err(SdkError::NotFound) => bail!("Tool `{name}` was not found in the registry"),
+                Err(e) => {
+                    return Err(e).with_context(|| format!("fetching versions of `{name}`"));
+                }
+            };
+            self.versions.insert(name.to_string(), versions);
+        }
+        Ok(&self.versions[name])
+    }
+
+    /// Newest non-yanked version of `name` matching `req`; prereleases only if `req` asks for them.
+    pub async fn newest(&mut self, name: &str, req: &VersionReq) -> Result<Option<Version>> {
+        Ok(self
+            .versions(name)
+            .await?
+            .iter()
+            .filter(|v| !v.yanked)
+            .filter_map(|v| Version::parse(&v.version).ok())
+            .filter(|v| req.matches(v))
+            .max())
+    }
+
+    async fn pick(&mut self, name: &str, reqs: &[Requirement]) -> Result<LockedTool> {
+        check_name(name)?;
+        if !self.opts.update.applies_to(name)
+            && let Some(locked) = self.previous.and_then(|l| l.get(name))
+            && satisfies_all(locked, reqs)
+        {
+            return Ok(locked.clone());
+        }
+
+        if self.opts.offline {
+            bail!(
+                "`{name}` ({}) is not pinned in agentpm.lock and the registry may not be contacted",
+                describe(reqs)
+            );
+        }
+
+        let source = self.client.base_url().to_string();
+        let best = self
+            .versions(name)
+            .await?
+            .iter()
+            .filter(|v| !v.yanked)
+            .filter_map(|v| Some((Version::parse(&v.version).ok()?, v)))
+            .filter(|(version, _)| reqs.iter().all(|r| r.req.matches(version)))
+            .max_by(|(a, _), (b, _)| a.cmp(b));
+
+        match best {
+            // The name ends up as a directory name; never take the registry's word for it
+            Some((_, v)) if v.name != name => bail!(
+                "the registry answered for {:?} when asked about `{name}`",
+                v.name
+            ),
+            Some((_, v)) => Ok(locked_from(v, source)),
+            None => bail!("No version of `{name}` matches {}", describe(reqs)),
+        }
+    }
+}
+
+/// Walk from the manifest through the current picks, gathering every range placed on each tool.
+fn collect_requirements(
+    root: &str,
+    roots: &[(String, VersionReq)],
+    picks: &BTreeMap<String, LockedTool>,
+) -> Result<BTreeMap<String, Vec<Requirement>>> {
+    let mut reqs: BTreeMap<String, Vec<Requirement>> = BTreeMap::new();
+    let mut queue: VecDeque<(String, VersionReq, String)> = roots
+        .iter()
+        .map(|(name, req)| (name.clone(), req.clone(), root.to_string()))
+        .collect();
+
+    while let Some((name, req, from)) = queue.pop_front() {
+        let first_seen = !reqs.contains_key(&name);
+        reqs.entry(name.clone())
+            .or_default()
+            .push(Requirement { req, from });
+
+        if first_seen && let Some(pick) = picks.get(&name) {
+            for (dep, dep_req) in pick.requirements()? {
+                queue.push_back((dep, dep_req, format!("{}@{}", pick.name, pick.version)));
+            }
+        }
+    }
+    Ok(reqs)
+}
+
+fn satisfies_all(tool: &LockedTool, reqs: &[Requirement]) -> bool {
+    match Version::parse(&tool.version) {
+        Ok(v) => reqs.iter().all(|r| r.req.matches(&v)),
+        Err(_) => false,
+    }
+}
+
+fn describe(reqs: &[Requirement]) -> String {
+    reqs.iter()
+        .map(|r| format!("{} (required by {})", r.req, r.from))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn locked_from(v: &ToolVersion, source: String) -> LockedTool {
+    LockedTool {
+        name: v.name.clone(),
+        version: v.version.clone(),
+        source,
+        digest: v.digest.clone(),
+        dependencies: v
+            .dependencies
+            .iter()
+            .map(|d| match d.version.as_deref() {
+                Some(range) if !range.is_empty() => format!("{}@{}", d.name, range),
+                _ => d.name.clone(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked(name: &str, version: &str, dependencies: &[&str]) -> LockedTool {
+        LockedTool {
+            name: name.into(),
+            version: version.into(),
+            source: "http://registry.invalid".into(),
+            digest: format!("sha256:{name}-{version}"),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    fn agent(tools: &[&str]) -> Manifest {
+        serde_json::from_value(serde_json::json!({
+            "kind": "agent",
+            "name": "my-agent",
+            "version": "0.1.0",
+            "tools": tools,
+        }))
+        .unwrap()
+    }
+
+    fn roots(manifest: &Manifest) -> Vec<(String, VersionReq)> {
+        manifest
+            .tools
+            .iter()
+            .map(|t| (t.name().to_string(), t.version_req().unwrap()))
+            .collect()
+    }
+
+    /// Never reached: offline resolution must not touch the registry.
+    fn client() -> AgentPmClient {
+        AgentPmClient::new("http://registry.invalid").unwrap()
+    }
+
+    fn offline() -> ResolveOptions {
+        ResolveOptions {
+            offline: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn conflicting_ranges_are_collected_with_who_asked() {
+        let manifest = agent(&["summarize@^2", "pipeline@^0.1"]);
+        let picks = BTreeMap::from([
+            (
+                "pipeline".into(),
+                locked("pipeline", "0.1.0", &["summarize@^1.0"]),
+            ),
+            ("summarize".into(), locked("summarize", "2.0.0", &[])),
+        ]);
+
+        let reqs = collect_requirements(&manifest.name, &roots(&manifest), &picks).unwrap();
+        let summarize = &reqs["summarize"];
+        assert_eq!(summarize.len(), 2);
+        assert!(!satisfies_all(&picks["summarize"], summarize));
+        assert_eq!(
+            describe(summarize),
+            "^2 (required by my-agent), ^1.0 (required by pipeline@0.1.0)"
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_locked_versions_that_satisfy_every_range() {
+        let previous = Lockfile::new(vec![
+            locked("pipeline", "0.1.0", &["summarize@^1.0"]),
+            locked("summarize", "1.2.0", &[]),
+        ]);
+        let client = client();
+        let lock = Resolver::new(&client, Some(&previous), offline())
+            .resolve(&agent(&["summarize@^1", "pipeline@^0.1"]))
+            .await
+            .unwrap();
+        assert_eq!(lock, previous);
+    }
+
+    #[tokio::test]
+    async fn locked_version_outside_a_range_is_not_reused() {
+        let previous = Lockfile::new(vec![locked("summarize", "1.2.0", &[])]);
+        let client = client();
+        let err = Resolver::new(&client, Some(&previous), offline())
+            .resolve(&agent(&["summarize@^2"]))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`summarize` (^2 (required by my-agent)) is not pinned in agentpm.lock and the registry may not be contacted"
+        );
+    }
+
+    #[tokio::test]
+    async fn transitive_conflict_with_the_lock_is_reported() {
+        // pipeline keeps summarize on ^1, so the agent's ^2 can't be met by either pick
+        let previous = Lockfile::new(vec![
+            locked("pipeline", "0.1.0", &["summarize@^1.0"]),
+            locked("summarize", "2.0.0", &[]),
+        ]);
+        let client = client();
+        let err = Resolver::new(&client, Some(&previous), offline())
+            .resolve(&agent(&["summarize@^2", "pipeline@^0.1"]))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("^2 (required by my-agent), ^1.0 (required by pipeline@0.1.0)"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_names_that_are_not_package_names() {
+        let client = client();
+        let err = Resolver::new(&client, None, offline())
+            .resolve(&agent(&["../escape"]))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("is not a valid tool name"),
+            "{err}"
+        );
+    }
+}
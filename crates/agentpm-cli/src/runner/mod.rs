@@ -0,0 +1,391 @@
+pub mod runtime;
+pub mod sandbox;
+pub mod steps;
+
+use crate::env::{Redactor, ToolEnv};
+use crate::manifest::{Manifest, ManifestKind};
+use crate::prelude::*;
+use crate::util::schema_io::{compile_schema, schema_violations};
+use anyhow::{anyhow, bail};
+use jsonschema::JSONSchema;
+use sandbox::Sandbox;
+use serde_json::Value;
+use std::{
+    io::{Read, Write},
+    path::PathBuf,
+    process::{Child, ExitStatus, Output, Stdio},
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// A `kind: tool` package that can be executed: JSON in on stdin, JSON out on stdout.
+pub struct ToolPackage {
+    pub dir: PathBuf,
+    pub manifest: Manifest,
+    inputs: JSONSchema,
+    outputs: JSONSchema,
+}
+
+/// How to execute a tool.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Confine the tool (Linux only)
+    pub sandbox: Option<Sandbox>,
+    /// Kill the tool once it has run this long
+    pub timeout: Option<Duration>,
+    /// `.env` file consulted for declared variables missing from the environment
+    pub dotenv: Option<PathBuf>,
+}
+
+/// Everything observed about one execution.
+#[derive(Debug)]
+pub struct RunOutcome {
+    /// Parsed stdout, if it was JSON
+    pub output: Option<Value>,
+    pub stdout: String,
+    pub stderr: String,
+    pub status: ExitStatus,
+    pub duration: Duration,
+    /// Why the run counts as failed; empty on success
+    pub problems: Vec<String>,
+    /// Masks the tool's secret variables; `stdout`, `stderr` and `problems` are already redacted,
+    /// `output` is not (it may feed another tool)
+    pub secrets: Redactor,
+}
+
+impl ToolPackage {
+    /// Check `manifest` describes a runnable tool and compile its `inputs`/`outputs` schemas.
+    pub fn new(dir: PathBuf, manifest: Manifest) -> Result<Self> {
+        if manifest.kind != ManifestKind::Tool {
+            bail!("`{}` is not a `kind: tool` manifest", manifest.name);
+        }
+        let schema = |field: &str, value: &Option<Value>| -> Result<JSONSchema> {
+            let value = value
+                .as_ref()
+                .ok_or_else(|| anyhow!("`{}` has no `{field}` schema", manifest.name))?;
+            compile_schema(value).with_context(|| format!("`{field}` of `{}`", manifest.name))
+        };
+        let inputs = schema("inputs", &manifest.inputs)?;
+        let outputs = schema("outputs", &manifest.outputs)?;
+        Ok(Self {
+            dir,
+            manifest,
+            inputs,
+            outputs,
+        })
+    }
+
+    pub fn entrypoint(&self) -> Result<PathBuf> {
+        let rel = self
+            .manifest
+            .entrypoint
+            .as_deref()
+            .ok_or_else(|| anyhow!("`{}` has no `entrypoint`", self.manifest.name))?;
+        let path = self.dir.join(rel);
+        if !path.is_file() {
+            bail!("entrypoint {} does not exist", path.display());
+        }
+        Ok(path)
+    }
+
+    /// Violations of the `inputs` schema by `input` (empty when valid).
+    pub fn check_input(&self, input: &Value) -> Vec<String> {
+        schema_violations(&self.inputs, input)
+    }
+
+    /// Validate `input`, run the entrypoint and validate what it prints.
+    /// Errors mean the tool could not be started; a tool that ran but misbehaved
+    /// is reported through `RunOutcome::problems`.
+    pub fn run(&self, input: &Value, opts: &RunOptions) -> Result<RunOutcome> {
+        let violations = self.check_input(input);
+        if !violations.is_empty() {
+            bail!(
+                "input does not match the `inputs` schema of `{}`:\n  {}",
+                self.manifest.name,
+                violations.join("\n  ")
+            );
+        }
+
+        let mut env = ToolEnv::resolve(
+            &self.manifest.name,
+            &self.manifest.env,
+            opts.dotenv.as_deref(),
+        )?;
+        // The sandbox lets through only what `permissions.env` grants, wherever it comes from
+        if let Some(sandbox) = &opts.sandbox {
+            for name in env.retain_permitted(&sandbox.permissions.env) {
+                eprintln!(
+                    "warning: `{}` declares `{name}` in `env` but not in `permissions.env`; --sandbox withholds it",
+                    self.manifest.name
+                );
+            }
+        }
+        let entrypoint = self.entrypoint()?;
+        let mut launch = runtime::resolve(&entrypoint, self.manifest.runtime)?;
+        if opts.sandbox.is_some() {
+            launch = launch.without_shims();
+        }
+        let mut cmd = launch.command();
+        // Its own process group, so the timeout can kill whatever the tool starts too
+        #[cfg(target_os = "linux")]
+        if opts.timeout.is_some() {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        cmd.current_dir(&self.dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let _scratch = match &opts.sandbox {
+            Some(sandbox) => Some(sandbox.apply(&mut cmd, &launch, &self.dir)?),
+            None => None,
+        };
+        cmd.envs(&env.vars);
+        debug!(
+            "running {} ({:?} runtime, sandbox: {:?})",
+            env.secrets.redact(&format!("{cmd:?}")),
+            launch.runtime,
+            opts.sandbox
+        );
+
+        let started = Instant::now();
+        let cpu_before = sandbox::children_cpu_time();
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("starting {}", launch.program.display()))?;
+
+        // Feed stdin from a thread so a tool that writes before reading can't deadlock us
+        let payload = serde_json::to_vec(input)?;
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = std::thread::spawn(move || {
+            // A tool may exit without reading its input; that's its business
+            let _ = stdin.write_all(&payload);
+        });
+        let (out, timed_out) = wait_with_timeout(child, opts.timeout)?;
+        let _ = writer.join();
+        let duration = started.elapsed();
+        let cpu_used = sandbox::children_cpu_time().saturating_sub(cpu_before);
+
+        let raw_stdout = String::from_utf8_lossy(&out.stdout);
+        let stdout = env.secrets.redact(&raw_stdout);
+        let stderr = env.secrets.redact(&String::from_utf8_lossy(&out.stderr));
+        let mut problems = Vec::new();
+        if let Some(limit) = opts.timeout.filter(|_| timed_out) {
+            problems.push(format!(
+                "tool was killed after exceeding its wall-clock limit of {}s (raise it with --timeout)",
+                limit.as_secs_f64()
+            ));
+        } else if let Some(why) = opts
+            .sandbox
+            .as_ref()
+            .and_then(|s| s.explain(&out.status, &stderr, cpu_used))
+        {
+            problems.push(why);
+        } else if !out.status.success() {
+            problems.push(format!("tool exited with {}", out.status));
+        }
+
+        let output = match serde_json::from_str::<Value>(raw_stdout.trim()) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                if out.status.success() {
+                    problems.push(format!("stdout is not valid JSON: {e}"));
+                }
+                None
+            }
+        };
+        if let Some(output) = &output {
+            for v in schema_violations(&self.outputs, output) {
+                problems.push(format!(
+                    "output does not match `outputs`: {}",
+                    env.secrets.redact(&v)
+                ));
+            }
+        }
+
+        Ok(RunOutcome {
+            output,
+            stdout,
+            stderr,
+            status: out.status,
+            duration,
+            problems,
+            secrets: env.secrets,
+        })
+    }
+}
+
+/// How long the pipes may stay open once the tool's process group has been killed.
+const DRAIN_GRACE: Duration = Duration::from_secs(1);
+
+/// Like `Child::wait_with_output`, but kill the child, and everything it started, once
+/// `timeout` elapses. Returns the output and whether the child was killed.
+fn wait_with_timeout(mut child: Child, timeout: Option<Duration>) -> Result<(Output, bool)> {
+    let Some(timeout) = timeout else {
+        return Ok((child.wait_with_output()?, false));
+    };
+
+    let stdout = Drain::start(child.stdout.take());
+    let stderr = Drain::start(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            timed_out = true;
+            kill_group(&mut child);
+            break child.wait()?;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    // Processes the tool left behind may hold the pipes open; they get the same deadline
+    let drains = [&stdout, &stderr];
+    if timed_out || !Drain::wait_all(&drains, deadline) {
+        kill_group(&mut child);
+        // Anything that escaped the group (e.g. with setsid) keeps its pipe; stop listening
+        Drain::wait_all(&drains, Instant::now() + DRAIN_GRACE);
+    }
+
+    let output = Output {
+        status,
+        stdout: stdout.output(),
+        stderr: stderr.output(),
+    };
+    Ok((output, timed_out))
+}
+
+/// A pipe read to its end on a background thread; what arrived so far can be taken at
+/// any time, so a pipe that never closes can't hang the runner.
+struct Drain {
+    buf: Arc<Mutex<Vec<u8>>>,
+    thread: JoinHandle<()>,
+}
+
+impl Drain {
+    fn start(pipe: Option<impl Read + Send + 'static>) -> Self {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&buf);
+        let thread = std::thread::spawn(move || {
+            let Some(mut pipe) = pipe else { return };
+            let mut chunk = [0u8; 8192];
+            loop {
+                match pipe.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(n) => sink
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .extend_from_slice(&chunk[..n]),
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(_) => break,
+                }
+            }
+        });
+        Self { buf, thread }
+    }
+
+    /// Wait until every drain has seen its pipe close, or `until`; true if they all did.
+    fn wait_all(drains: &[&Drain], until: Instant) -> bool {
+        loop {
+            if drains.iter().all(|d| d.thread.is_finished()) {
+                return true;
+            }
+            if Instant::now() >= until {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn output(&self) -> Vec<u8> {
+        self.buf.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Kill the child and, where it leads its own process group, everything it started.
+fn kill_group(child: &mut Child) {
+    #[cfg(target_os = "linux")]
+    // SAFETY: a plain syscall; the group was created for this child by `process_group(0)`
+    unsafe {
+        libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+impl RunOutcome {
+    pub fn ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::permissions::Permissions;
+    use serde_json::json;
+    use std::fs;
+
+    /// A shell tool echoing two variables from its `.env`, only one of them permitted.
+    fn env_tool(dir: &std::path::Path) -> ToolPackage {
+        let manifest = json!({
+            "kind": "tool",
+            "name": "env-echo",
+            "version": "0.1.0",
+            "entrypoint": "run.sh",
+            "runtime": "shell",
+            "inputs": { "type": "object" },
+            "outputs": { "type": "object" },
+            "env": { "API_TOKEN": { "secret": true }, "REGION": {} },
+            "permissions": { "env": ["REGION"] },
+        });
+        fs::write(dir.join("agent.json"), manifest.to_string()).unwrap();
+        fs::write(
+            dir.join("run.sh"),
+            "printf '{\"token\": \"%s\", \"region\": \"%s\"}' \"$API_TOKEN\" \"$REGION\"\n",
+        )
+        .unwrap();
+        fs::write(dir.join(".env"), "API_TOKEN=tok-123456\nREGION=eu-west\n").unwrap();
+        ToolPackage::new(dir.to_path_buf(), serde_json::from_value(manifest).unwrap()).unwrap()
+    }
+
+    fn options(dir: &std::path::Path, permissions: Option<Permissions>) -> RunOptions {
+        RunOptions {
+            sandbox: permissions.map(|permissions| Sandbox {
+                cpu_time: 5,
+                memory: 256,
+                permissions,
+            }),
+            timeout: Some(Duration::from_secs(10)),
+            dotenv: Some(dir.join(".env")),
+        }
+    }
+
+    #[test]
+    fn sandbox_withholds_env_not_in_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = env_tool(dir.path());
+
+        let plain = tool.run(&json!({}), &options(dir.path(), None)).unwrap();
+        assert_eq!(
+            plain.output,
+            Some(json!({ "token": "tok-123456", "region": "eu-west" }))
+        );
+
+        let permissions = tool.manifest.permissions.clone();
+        let sandboxed = match tool.run(&json!({}), &options(dir.path(), Some(permissions))) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                eprintln!("skipping: {e:#}");
+                return;
+            }
+        };
+        assert_eq!(
+            sandboxed.output,
+            Some(json!({ "token": "", "region": "eu-west" }))
+        );
+    }
+}
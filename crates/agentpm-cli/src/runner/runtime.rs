@@ -0,0 +1,291 @@
+use crate::prelude::*;
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    fs,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// What executes an entrypoint. Declared via `runtime` in agent.json, or detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Runtime {
+    Python,
+    Node,
+    Shell,
+    /// Executed directly (compiled binaries, or scripts relying on the OS to read the shebang)
+    Binary,
+}
+
+/// A resolved way to start an entrypoint: `program [args...] entrypoint`.
+#[derive(Debug, Clone)]
+pub struct Launch {
+    pub runtime: Runtime,
+    pub program: PathBuf,
+    pub args: Vec<OsString>,
+}
+
+impl Runtime {
+    /// Guess from the file extension.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "py" => Some(Runtime::Python),
+            "js" | "mjs" | "cjs" => Some(Runtime::Node),
+            "sh" => Some(Runtime::Shell),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Runtime::Python => "Python",
+            Runtime::Node => "Node.js",
+            Runtime::Shell => "a POSIX shell",
+            Runtime::Binary => "nothing",
+        }
+    }
+
+    /// Interpreters to look for on PATH, in order of preference.
+    fn interpreters(self) -> &'static [&'static str] {
+        match self {
+            Runtime::Python => &["python3", "python"],
+            Runtime::Node => &["node"],
+            Runtime::Shell => &["sh", "bash"],
+            Runtime::Binary => &[],
+        }
+    }
+
+    /// Environment variable that overrides the interpreter path.
+    fn override_var(self) -> Option<&'static str> {
+        match self {
+            Runtime::Python => Some("AGENTPM_PYTHON"),
+            Runtime::Node => Some("AGENTPM_NODE"),
+            Runtime::Shell => Some("AGENTPM_SHELL"),
+            Runtime::Binary => None,
+        }
+    }
+
+    fn install_hint(self) -> &'static str {
+        match self {
+            Runtime::Python => "Install Python 3 (https://www.python.org/downloads/)",
+            Runtime::Node => "Install Node.js (https://nodejs.org/)",
+            Runtime::Shell => "Install a POSIX shell (on Windows: Git Bash or WSL)",
+            Runtime::Binary => "",
+        }
+    }
+}
+
+/// Whether `resolve` would know how to start `entrypoint`: a runtime is declared, a
+/// shebang or extension names one, or the file is executable. Doesn't look for interpreters.
+pub fn is_startable(entrypoint: &Path, declared: Option<Runtime>) -> bool {
+    let runtime = match declared {
+        Some(r) => r,
+        None if read_shebang(entrypoint).is_some() => return true,
+        None => Runtime::from_extension(entrypoint).unwrap_or(Runtime::Binary),
+    };
+    runtime != Runtime::Binary || is_executable(entrypoint)
+}
+
+/// Decide how to start `entrypoint`.
+/// Precedence: declared `runtime` > shebang > file extension > run it directly.
+pub fn resolve(entrypoint: &Path, declared: Option<Runtime>) -> Result<Launch> {
+    let shebang = read_shebang(entrypoint);
+
+    let runtime = match declared {
+        Some(r) => r,
+        None => {
+            if let Some((interpreter, args)) = shebang {
+                return launch_shebang(entrypoint, &interpreter, args);
+            }
+            Runtime::from_extension(entrypoint).unwrap_or(Runtime::Binary)
+        }
+    };
+
+    if runtime == Runtime::Binary {
+        if !is_executable(entrypoint) {
+            bail!(
+                "entrypoint {} is not executable and its runtime can't be detected. \
+                 Run `chmod +x` on it, or set `runtime` in agent.json (python, node, shell, binary).",
+                entrypoint.display()
+            );
+        }
+        return Ok(Launch {
+            runtime,
+            program: entrypoint.to_path_buf(),
+            args: Vec::new(),
+        });
+    }
+
+    Ok(Launch {
+        runtime,
+        program: find_interpreter(runtime, entrypoint)?,
+        args: vec![entrypoint.as_os_str().to_owned()],
+    })
+}
+
+impl Launch {
+    pub fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args);
+        cmd
+    }
+
+    /// Ask the interpreter where its executable really lives, bypassing version-manager shims
+    /// (pyenv, asdf, volta...) that would otherwise start more programs before the real one.
+    /// Keeps the launch as is when the interpreter can't tell.
+    pub fn without_shims(mut self) -> Self {
+        let probe: &[&str] = match self.runtime {
+            Runtime::Python => &["-c", "import sys; print(sys.executable)"],
+            Runtime::Node => &["-p", "process.execPath"],
+            Runtime::Shell | Runtime::Binary => return self,
+        };
+        let real = Command::new(&self.program)
+            .args(probe)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| PathBuf::from(String::from_utf8_lossy(&out.stdout).trim()))
+            .filter(|path| path.is_file());
+        if let Some(real) = real {
+            debug!("{} resolves to {}", self.program.display(), real.display());
+            self.program = real;
+        }
+        self
+    }
+}
+
+/// Honor `#!` ourselves (also works on Windows and without the executable bit).
+/// `#!/usr/bin/env python3` looks up `python3` on PATH; an absolute interpreter that doesn't
+/// exist on this machine (e.g. /usr/local/bin/node) is looked up on PATH by name.
+fn launch_shebang(entrypoint: &Path, interpreter: &str, mut args: Vec<String>) -> Result<Launch> {
+    let program = if Path::new(interpreter).file_name() == Some("env".as_ref()) {
+        if args.first().is_some_and(|a| a == "-S") {
+            args.remove(0);
+        }
+        if args.is_empty() {
+            bail!(
+                "the shebang of {} runs `env` without naming a program",
+                entrypoint.display()
+            );
+        }
+        args.remove(0)
+    } else {
+        interpreter.to_string()
+    };
+
+    let path = Path::new(&program);
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(&program);
+    let runtime = runtime_for_program(name);
+    let found = if path.is_absolute() && path.is_file() {
+        Some(path.to_path_buf())
+    } else {
+        which(name)
+    };
+    let Some(found) = found else {
+        let hint = runtime
+            .map(|r| format!(" {}.", r.install_hint()))
+            .unwrap_or_default();
+        bail!(
+            "entrypoint {} asks for `{program}` in its shebang, but `{name}` was not found on PATH.{hint}",
+            entrypoint.display()
+        );
+    };
+
+    let mut args: Vec<OsString> = args.into_iter().map(OsString::from).collect();
+    args.push(entrypoint.as_os_str().to_owned());
+    Ok(Launch {
+        runtime: runtime.unwrap_or(Runtime::Binary),
+        program: found,
+        args,
+    })
+}
+
+fn runtime_for_program(program: &str) -> Option<Runtime> {
+    let stem = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    match stem {
+        "python" => Some(Runtime::Python),
+        "node" | "nodejs" => Some(Runtime::Node),
+        "sh" | "bash" | "dash" | "zsh" => Some(Runtime::Shell),
+        _ => None,
+    }
+}
+
+fn find_interpreter(runtime: Runtime, entrypoint: &Path) -> Result<PathBuf> {
+    if let Some(var) = runtime.override_var()
+        && let Some(path) = std::env::var_os(var)
+    {
+        let path = PathBuf::from(path);
+        if !path.is_file() {
+            bail!("{var} points to {}, which does not exist", path.display());
+        }
+        return Ok(path);
+    }
+
+    if let Some(found) = runtime.interpreters().iter().find_map(|name| which(name)) {
+        return Ok(found);
+    }
+
+    bail!(
+        "entrypoint {} needs {}, but none of `{}` was found on PATH. {}, or set {} to the interpreter's path.",
+        entrypoint.display(),
+        runtime.name(),
+        runtime.interpreters().join("`, `"),
+        runtime.install_hint(),
+        runtime.override_var().unwrap_or_default()
+    )
+}
+
+/// `(interpreter, args)` from a `#!` first line, if any.
+pub fn read_shebang(path: &Path) -> Option<(String, Vec<String>)> {
+    let mut first = Vec::new();
+    // 256 bytes is plenty for any shebang and keeps us from reading binaries whole
+    BufReader::new(fs::File::open(path).ok()?)
+        .take(256)
+        .read_until(b'\n', &mut first)
+        .ok()?;
+    let line = String::from_utf8_lossy(first.strip_prefix(b"#!")?).into_owned();
+    let mut words = line.split_whitespace().map(String::from);
+    let interpreter = words.next()?;
+    Some((interpreter, words.collect()))
+}
+
+/// Look `program` up on PATH (with PATHEXT-style suffixes on Windows).
+pub fn which(program: &str) -> Option<PathBuf> {
+    let candidate = Path::new(program);
+    if candidate.components().count() > 1 {
+        return candidate.is_file().then(|| candidate.to_path_buf());
+    }
+    let exts: &[&str] = if cfg!(windows) {
+        &["", ".exe", ".cmd", ".bat"]
+    } else {
+        &[""]
+    };
+    std::env::split_paths(&std::env::var_os("PATH")?).find_map(|dir| {
+        exts.iter()
+            .map(|ext| dir.join(format!("{program}{ext}")))
+            .find(|p| p.is_file() && is_executable(p))
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| {
+        matches!(
+            e.to_ascii_lowercase().as_str(),
+            "exe" | "cmd" | "bat" | "com"
+        )
+    })
+}
@@ -0,0 +1,641 @@
+//! Opt-in confinement for local tool runs (`agentpm run --sandbox`), Linux only.
+//!
+//! - Landlock limits the filesystem to system directories (read-only), the interpreter's
+//!   prefix (read-only), the package directory (read-only), a scratch directory and the
+//!   paths in `permissions.filesystem`; only the interpreter and `permissions.subprocess`
+//!   programs may be executed.
+//! - A fresh network namespace cuts the network unless `permissions.network` is declared.
+//!   That is all or nothing: declaring one host gives the tool the whole network.
+//! - The environment is cleared down to a baseline plus `permissions.env`.
+//! - rlimits cap CPU time and memory; the wall-clock limit is enforced by the runner.
+
+use super::runtime::Launch;
+use crate::permissions::Permissions;
+use crate::prelude::*;
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+    time::Duration,
+};
+
+/// Variables every tool gets (locale and terminal settings); the rest must be declared in
+/// `permissions.env`.
+const PASSTHROUGH_ENV: &[&str] = &["PATH", "LANG", "LANGUAGE", "TZ", "TERM", "NO_COLOR"];
+
+/// Limits for one sandboxed run.
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    /// CPU seconds before the tool is sent SIGXCPU
+    pub cpu_time: u64,
+    /// Cap on heap and other private writable memory, in MiB
+    pub memory: u64,
+    /// What the manifest declares; anything else is denied
+    pub permissions: Permissions,
+}
+
+/// Scratch directory handed to a sandboxed tool as HOME and TMPDIR; removed on drop.
+pub struct Scratch {
+    pub dir: PathBuf,
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+impl Sandbox {
+    /// Confine `cmd`, which starts `launch` from `package_dir`.
+    /// The returned scratch directory must outlive the child.
+    pub fn apply(&self, cmd: &mut Command, launch: &Launch, package_dir: &Path) -> Result<Scratch> {
+        let scratch = Scratch {
+            dir: std::env::temp_dir().join(format!(
+                "agentpm-run-{}-{}",
+                std::process::id(),
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos()
+            )),
+        };
+        std::fs::create_dir_all(&scratch.dir)
+            .with_context(|| format!("create {}", scratch.dir.display()))?;
+        // Only a baseline and the declared variables reach the tool
+        let passthrough: Vec<(String, String)> = std::env::vars()
+            .filter(|(key, _)| {
+                PASSTHROUGH_ENV.contains(&key.as_str())
+                    || key.starts_with("LC_")
+                    || self.permissions.env.contains(key)
+            })
+            .collect();
+        cmd.env_clear()
+            .envs(passthrough)
+            .env("HOME", &scratch.dir)
+            .env("TMPDIR", &scratch.dir)
+            .env("TMP", &scratch.dir)
+            .env("TEMP", &scratch.dir);
+
+        linux::confine(self, cmd, launch, package_dir, &scratch.dir)?;
+        Ok(scratch)
+    }
+
+    /// Explain an exit caused by one of the limits (the wall-clock limit is reported by the
+    /// runner). A limit is only blamed when its own signal says so: `cpu_used` is the CPU
+    /// time the tool consumed, since a SIGKILL may as well come from the OOM killer.
+    pub fn explain(&self, status: &ExitStatus, stderr: &str, cpu_used: Duration) -> Option<String> {
+        if status.success() {
+            return None;
+        }
+        match linux::termination(status) {
+            // Only RLIMIT_CPU sends SIGXCPU
+            Termination::CpuLimit => {
+                return Some(format!(
+                    "tool was killed after using its CPU time limit of {}s (raise it with --cpu-time)",
+                    self.cpu_time
+                ));
+            }
+            Termination::Killed if cpu_used >= Duration::from_secs(self.cpu_time + 1) => {
+                return Some(format!(
+                    "tool was killed at the hard CPU time limit of {}s (raise it with --cpu-time)",
+                    self.cpu_time + 1
+                ));
+            }
+            Termination::Killed | Termination::Crashed | Termination::Exited => {}
+        }
+        // RLIMIT_DATA makes allocations fail rather than sending a signal, so only the
+        // tool's own report of that failure points at the memory limit
+        let out_of_memory = [
+            "MemoryError",
+            "out of memory",
+            "Cannot allocate memory",
+            "bad_alloc",
+        ]
+        .iter()
+        .any(|needle| stderr.contains(needle));
+        if out_of_memory {
+            return Some(format!(
+                "tool most likely ran out of memory under the {} MiB limit (raise it with --memory)",
+                self.memory
+            ));
+        }
+        None
+    }
+}
+
+/// CPU time used so far by this process's reaped children; the difference across a run
+/// is what that run's tool consumed.
+pub fn children_cpu_time() -> Duration {
+    linux::children_cpu_time()
+}
+
+/// How a sandboxed tool that did not succeed came to stop.
+enum Termination {
+    /// SIGXCPU: the soft CPU limit
+    CpuLimit,
+    /// SIGKILL: the hard CPU limit, the OOM killer or anyone else
+    Killed,
+    /// SIGABRT/SIGSEGV/SIGBUS
+    Crashed,
+    Exited,
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{Launch, Sandbox, Termination};
+    use crate::permissions::FsAccess;
+    use crate::prelude::*;
+    use crate::runner::runtime::{Runtime, read_shebang, which};
+    use anyhow::anyhow;
+    use landlock::{
+        ABI, Access, AccessFs, CompatLevel, Compatible, Ruleset, RulesetAttr, RulesetCreatedAttr,
+        path_beneath_rules,
+    };
+    use std::{
+        ffi::CString,
+        io,
+        os::unix::process::{CommandExt, ExitStatusExt},
+        path::{Path, PathBuf},
+        process::{Command, ExitStatus},
+        time::Duration,
+    };
+
+    /// Read-only system locations every runtime needs (shared libraries, certificates, locale data).
+    const SYSTEM_DIRS: &[&str] = &[
+        "/usr",
+        "/lib",
+        "/lib32",
+        "/lib64",
+        "/bin",
+        "/sbin",
+        "/etc",
+        "/opt",
+        "/nix/store",
+    ];
+    /// Devices a tool may read and write.
+    const DEVICES: &[&str] = &[
+        "/dev/null",
+        "/dev/zero",
+        "/dev/full",
+        "/dev/random",
+        "/dev/urandom",
+    ];
+
+    pub fn termination(status: &ExitStatus) -> Termination {
+        match status.signal() {
+            Some(libc::SIGXCPU) => Termination::CpuLimit,
+            Some(libc::SIGKILL) => Termination::Killed,
+            Some(libc::SIGABRT | libc::SIGSEGV | libc::SIGBUS) => Termination::Crashed,
+            _ => Termination::Exited,
+        }
+    }
+
+    pub fn children_cpu_time() -> Duration {
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        // SAFETY: `usage` is a valid, writable rusage
+        if unsafe { libc::getrusage(libc::RUSAGE_CHILDREN, &mut usage) } != 0 {
+            return Duration::ZERO;
+        }
+        let time = |t: libc::timeval| {
+            Duration::from_secs(t.tv_sec as u64) + Duration::from_micros(t.tv_usec as u64)
+        };
+        time(usage.ru_utime) + time(usage.ru_stime)
+    }
+
+    pub fn confine(
+        sandbox: &Sandbox,
+        cmd: &mut Command,
+        launch: &Launch,
+        package_dir: &Path,
+        scratch: &Path,
+    ) -> Result<()> {
+        let abi = ABI::V6;
+        let perms = &sandbox.permissions;
+        let mut read_only: Vec<PathBuf> = SYSTEM_DIRS.iter().map(PathBuf::from).collect();
+        read_only.push(package_dir.to_path_buf());
+        read_only.extend(interpreter_prefixes(launch, package_dir));
+        let mut read_write = vec![scratch.to_path_buf()];
+        for fs in &perms.filesystem {
+            let Some(path) = fs.resolve() else { continue };
+            match fs.access {
+                FsAccess::Read => read_only.push(path),
+                FsAccess::Write => read_write.push(path),
+            }
+        }
+
+        // Only the interpreter (and declared programs) may be executed, unless `subprocess` has `*`
+        let mut read = AccessFs::from_read(abi);
+        let mut write = AccessFs::from_all(abi);
+        let mut executables = exec_chain(&launch.program);
+        if perms.any_subprocess() {
+            executables.clear();
+        } else {
+            read.remove(AccessFs::Execute);
+            write.remove(AccessFs::Execute);
+            for program in &perms.subprocess {
+                match which(program) {
+                    Some(path) => executables.extend(exec_chain(&path)),
+                    None => debug!("declared subprocess `{program}` is not on PATH"),
+                }
+            }
+        }
+
+        // Landlock v1 is required; newer rights (truncate, refer, ioctl) are added when the kernel has them
+        let mut ruleset = Some(
+            Ruleset::default()
+                .set_compatibility(CompatLevel::HardRequirement)
+                .handle_access(AccessFs::from_all(ABI::V1))
+                .and_then(|r| {
+                    r.set_compatibility(CompatLevel::BestEffort)
+                        .handle_access(AccessFs::from_all(abi))
+                })
+                .and_then(|r| r.create())
+                .map_err(|e| {
+                    anyhow!(
+                        "--sandbox needs Landlock (Linux 5.13+ with `landlock` in the enabled LSMs): {e}"
+                    )
+                })?
+                .add_rules(path_beneath_rules(&read_only, read))?
+                .add_rules(path_beneath_rules(DEVICES, AccessFs::from_file(abi)))?
+                .add_rules(path_beneath_rules(&read_write, write))?
+                .add_rules(path_beneath_rules(
+                    &executables,
+                    AccessFs::Execute | AccessFs::ReadFile,
+                ))?,
+        );
+
+        let cpu = sandbox.cpu_time;
+        let memory = sandbox.memory.saturating_mul(1024 * 1024);
+        // Everything the child needs is allocated here: after fork only async-signal-safe calls are allowed
+        let network = perms.any_network();
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        let uid_map = (
+            CString::new("/proc/self/uid_map")?,
+            CString::new(format!("{uid} {uid} 1"))?,
+        );
+        let gid_map = (
+            CString::new("/proc/self/gid_map")?,
+            CString::new(format!("{gid} {gid} 1"))?,
+        );
+        let setgroups = (CString::new("/proc/self/setgroups")?, CString::new("deny")?);
+
+        let pre_exec = move || -> io::Result<()> {
+            check(unsafe { libc::setrlimit(libc::RLIMIT_CPU, &rlimit(cpu, cpu + 1)) })?;
+            check(unsafe { libc::setrlimit(libc::RLIMIT_DATA, &rlimit(memory, memory)) })?;
+            if !network {
+                check(unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) })?;
+                // Keep our own uid/gid inside the new user namespace instead of `nobody`
+                write_file(&setgroups.0, &setgroups.1)?;
+                write_file(&uid_map.0, &uid_map.1)?;
+                write_file(&gid_map.0, &gid_map.1)?;
+            }
+            match ruleset.take() {
+                Some(ruleset) => ruleset
+                    .restrict_self()
+                    .map(drop)
+                    .map_err(|_| io::Error::from_raw_os_error(libc::EPERM)),
+                None => Ok(()),
+            }
+        };
+        // SAFETY: the closure only makes raw syscalls on data prepared before the fork
+        unsafe { cmd.pre_exec(pre_exec) };
+        Ok(())
+    }
+
+    /// Every file the kernel executes to start `program`: the program itself, the ELF
+    /// interpreter (ld.so) of binaries, and the interpreters named by `#!` lines.
+    fn exec_chain(program: &Path) -> Vec<PathBuf> {
+        let mut chain: Vec<PathBuf> = Vec::new();
+        let mut next = Some(program.to_path_buf());
+        while let Some(path) = next.take() {
+            if chain.contains(&path) || chain.len() > 8 {
+                break;
+            }
+            if let Ok(real) = path.canonicalize()
+                && real != path
+            {
+                chain.push(real);
+            }
+            chain.push(path.clone());
+            if let Some(ld) = elf_interpreter(&path) {
+                chain.push(ld);
+            }
+            if let Some((interpreter, args)) = read_shebang(&path) {
+                let interpreter = PathBuf::from(interpreter);
+                next = if interpreter.file_name() == Some("env".as_ref()) {
+                    chain.extend(exec_chain(&interpreter));
+                    args.iter()
+                        .find(|a| !a.starts_with('-'))
+                        .and_then(|a| which(a))
+                } else {
+                    Some(interpreter)
+                };
+            }
+        }
+        chain
+    }
+
+    /// The `PT_INTERP` path of a little-endian ELF executable.
+    fn elf_interpreter(path: &Path) -> Option<PathBuf> {
+        let bytes = std::fs::read(path).ok()?;
+        if bytes.get(..4)? != b"\x7fELF" || *bytes.get(5)? != 1 {
+            return None;
+        }
+        let u16_at = |at: usize| Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?));
+        let u32_at = |at: usize| Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+        let u64_at = |at: usize| Some(u64::from_le_bytes(bytes.get(at..at + 8)?.try_into().ok()?));
+        let wide = match bytes.get(4)? {
+            1 => false,
+            2 => true,
+            _ => return None,
+        };
+        let (phoff, phentsize, phnum) = if wide {
+            (u64_at(0x20)? as usize, u16_at(0x36)?, u16_at(0x38)?)
+        } else {
+            (u32_at(0x1c)? as usize, u16_at(0x2a)?, u16_at(0x2c)?)
+        };
+        for i in 0..phnum as usize {
+            let header = phoff + i * phentsize as usize;
+            if u32_at(header)? != 3 {
+                continue;
+            }
+            let (offset, size) = if wide {
+                (
+                    u64_at(header + 0x08)? as usize,
+                    u64_at(header + 0x20)? as usize,
+                )
+            } else {
+                (
+                    u32_at(header + 0x04)? as usize,
+                    u32_at(header + 0x10)? as usize,
+                )
+            };
+            let raw = bytes.get(offset..offset + size)?;
+            let raw = raw.strip_suffix(b"\0").unwrap_or(raw);
+            return Some(PathBuf::from(std::str::from_utf8(raw).ok()?));
+        }
+        None
+    }
+
+    /// Where an interpreter keeps its standard library: `<prefix>/bin/python3` -> `<prefix>`,
+    /// for both the path on PATH (e.g. a pyenv shim) and the file it resolves to. A binary
+    /// entrypoint is the tool itself, and a prefix holding the package would also expose its
+    /// siblings, so neither gets one.
+    fn interpreter_prefixes(launch: &Launch, package_dir: &Path) -> Vec<PathBuf> {
+        if !matches!(
+            launch.runtime,
+            Runtime::Python | Runtime::Node | Runtime::Shell
+        ) {
+            return Vec::new();
+        }
+        let package = package_dir
+            .canonicalize()
+            .unwrap_or_else(|_| package_dir.to_path_buf());
+        let mut found = Vec::new();
+        let canonical = launch.program.canonicalize().ok();
+        for path in std::iter::once(launch.program.as_path()).chain(canonical.as_deref()) {
+            if path.starts_with(package_dir) || path.starts_with(&package) {
+                continue;
+            }
+            if let Some(prefix) = path.parent().and_then(Path::parent)
+                && prefix != Path::new("/")
+                && !package_dir.starts_with(prefix)
+                && !package.starts_with(prefix)
+                && !found.iter().any(|p: &PathBuf| p == prefix)
+            {
+                found.push(prefix.to_path_buf());
+            }
+        }
+        found
+    }
+
+    fn rlimit(soft: u64, hard: u64) -> libc::rlimit {
+        libc::rlimit {
+            rlim_cur: soft as libc::rlim_t,
+            rlim_max: hard as libc::rlim_t,
+        }
+    }
+
+    fn check(ret: libc::c_int) -> io::Result<()> {
+        if ret != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn write_file(path: &CString, contents: &CString) -> io::Result<()> {
+        let bytes = contents.as_bytes();
+        unsafe {
+            let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let written = libc::write(fd, bytes.as_ptr().cast(), bytes.len());
+            libc::close(fd);
+            if written != bytes.len() as isize {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod linux {
+    use super::{Launch, Sandbox, Termination};
+    use crate::prelude::*;
+    use anyhow::bail;
+    use std::{
+        path::Path,
+        process::{Command, ExitStatus},
+        time::Duration,
+    };
+
+    pub fn termination(_status: &ExitStatus) -> Termination {
+        Termination::Exited
+    }
+
+    pub fn children_cpu_time() -> Duration {
+        Duration::ZERO
+    }
+
+    pub fn confine(
+        _sandbox: &Sandbox,
+        _cmd: &mut Command,
+        _launch: &Launch,
+        _package_dir: &Path,
+        _scratch: &Path,
+    ) -> Result<()> {
+        bail!("--sandbox is only supported on Linux")
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::runner::runtime::{self, Runtime};
+    use std::{fs, os::unix::fs::PermissionsExt, process::Stdio};
+
+    fn sandbox() -> Sandbox {
+        Sandbox {
+            cpu_time: 5,
+            memory: 256,
+            permissions: Permissions::default(),
+        }
+    }
+
+    /// Run `script` as the `binary` entrypoint of `<tools>/tool`, next to `<tools>/sibling`,
+    /// with `{extra}` in it replaced by an outside directory, for filesystem grants.
+    /// `None` when this kernel can't sandbox.
+    fn run_binary_in(
+        sandbox: &Sandbox,
+        extra: &Path,
+        script: &str,
+    ) -> Option<(ExitStatus, String, String)> {
+        let tools = tempfile::tempdir().unwrap();
+        let sibling = tools.path().join("sibling");
+        fs::create_dir(&sibling).unwrap();
+        fs::write(sibling.join("secret"), "hunter2\n").unwrap();
+
+        let package = tools.path().join("tool");
+        fs::create_dir(&package).unwrap();
+        let entrypoint = package.join("run");
+        fs::write(
+            &entrypoint,
+            script.replace("{extra}", &extra.display().to_string()),
+        )
+        .unwrap();
+        fs::set_permissions(&entrypoint, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let launch = runtime::resolve(&entrypoint, Some(Runtime::Binary)).unwrap();
+        assert_eq!(launch.program, entrypoint);
+        let mut cmd = launch.command();
+        cmd.current_dir(&package)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let _scratch = match sandbox.apply(&mut cmd, &launch, &package) {
+            Ok(scratch) => scratch,
+            Err(e) => {
+                eprintln!("skipping: {e:#}");
+                return None;
+            }
+        };
+        let out = cmd.output().unwrap();
+        Some((
+            out.status,
+            String::from_utf8_lossy(&out.stdout).into_owned(),
+            String::from_utf8_lossy(&out.stderr).into_owned(),
+        ))
+    }
+
+    fn run_binary(script: &str) -> Option<(ExitStatus, String)> {
+        let extra = tempfile::tempdir().unwrap();
+        run_binary_in(&sandbox(), extra.path(), script).map(|(status, stdout, _)| (status, stdout))
+    }
+
+    #[test]
+    fn binary_entrypoint_cannot_read_its_siblings() {
+        let Some((status, stdout)) = run_binary(
+            "#!/bin/sh\nread -r own < run && echo \"own: $own\"\nread -r line < ../sibling/secret && echo \"leaked: $line\"\nexit 0\n",
+        ) else {
+            return;
+        };
+        assert!(status.success());
+        assert!(stdout.contains("own: #!/bin/sh"), "{stdout}");
+        assert!(!stdout.contains("hunter2"), "{stdout}");
+    }
+
+    /// Writes `<what>: ok` for each probe that succeeds.
+    const PROBES: &str = "#!/bin/sh
+echo x > \"$HOME/file\" && echo scratch: ok
+echo x > package-file && echo package: ok
+echo x > {extra}/file && echo extra: ok
+cat run > /dev/null && echo exec: ok
+echo \"env: ${CARGO_PKG_NAME:-unset}\"
+exit 0
+";
+
+    #[test]
+    fn confines_writes_execs_and_env_to_what_is_declared() {
+        let extra = tempfile::tempdir().unwrap();
+        let Some((status, stdout, _)) = run_binary_in(&sandbox(), extra.path(), PROBES) else {
+            return;
+        };
+        assert!(status.success());
+        assert!(stdout.contains("scratch: ok"), "{stdout}");
+        for denied in ["package: ok", "extra: ok", "exec: ok"] {
+            assert!(!stdout.contains(denied), "{stdout}");
+        }
+        assert!(stdout.contains("env: unset"), "{stdout}");
+    }
+
+    #[test]
+    fn declared_permissions_open_up_the_sandbox() {
+        let extra = tempfile::tempdir().unwrap();
+        let mut sandbox = sandbox();
+        sandbox.permissions = Permissions {
+            filesystem: vec![crate::permissions::FsPermission {
+                path: extra.path().display().to_string(),
+                access: crate::permissions::FsAccess::Write,
+            }],
+            env: vec!["CARGO_PKG_NAME".into()],
+            subprocess: vec!["cat".into()],
+            ..Permissions::default()
+        };
+        let Some((status, stdout, _)) = run_binary_in(&sandbox, extra.path(), PROBES) else {
+            return;
+        };
+        assert!(status.success());
+        for granted in ["scratch: ok", "extra: ok", "exec: ok"] {
+            assert!(stdout.contains(granted), "{stdout}");
+        }
+        assert!(!stdout.contains("package: ok"), "{stdout}");
+        // Set by cargo for the test process; passed on because it is declared
+        let name = std::env::var("CARGO_PKG_NAME").unwrap_or_else(|_| "unset".into());
+        assert!(stdout.contains(&format!("env: {name}")), "{stdout}");
+    }
+
+    #[test]
+    fn cpu_limit_stops_the_tool_and_is_explained() {
+        let extra = tempfile::tempdir().unwrap();
+        let mut sandbox = sandbox();
+        sandbox.cpu_time = 1;
+        let before = children_cpu_time();
+        let Some((status, _, stderr)) =
+            run_binary_in(&sandbox, extra.path(), "#!/bin/sh\nwhile :; do :; done\n")
+        else {
+            return;
+        };
+        let used = children_cpu_time().saturating_sub(before);
+        let why = sandbox.explain(&status, &stderr, used).unwrap();
+        assert!(why.contains("CPU time limit of 1s"), "{why}");
+    }
+
+    #[test]
+    fn explains_only_the_limits_that_were_hit() {
+        use std::os::unix::process::ExitStatusExt;
+        let sandbox = sandbox();
+        let signal = |sig: i32| ExitStatus::from_raw(sig);
+        let exit = |code: i32| ExitStatus::from_raw(code << 8);
+        let secs = Duration::from_secs;
+
+        let soft = sandbox
+            .explain(&signal(libc::SIGXCPU), "", secs(5))
+            .unwrap();
+        assert!(soft.contains("CPU time limit of 5s"), "{soft}");
+        let hard = sandbox
+            .explain(&signal(libc::SIGKILL), "", secs(6))
+            .unwrap();
+        assert!(hard.contains("hard CPU time limit of 6s"), "{hard}");
+        // A SIGKILL with CPU time to spare came from somewhere else
+        assert_eq!(sandbox.explain(&signal(libc::SIGKILL), "", secs(1)), None);
+        assert_eq!(sandbox.explain(&signal(libc::SIGSEGV), "", secs(0)), None);
+
+        let oom = sandbox
+            .explain(&exit(1), "Traceback ...\nMemoryError\n", secs(0))
+            .unwrap();
+        assert!(oom.contains("256 MiB"), "{oom}");
+        assert_eq!(sandbox.explain(&exit(1), "ValueError\n", secs(0)), None);
+        assert_eq!(sandbox.explain(&exit(0), "MemoryError\n", secs(0)), None);
+    }
+}
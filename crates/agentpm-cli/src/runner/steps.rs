@@ -0,0 +1,310 @@
+//! Local agent runs: a step file lists tool calls in order, and each call's input may
+//! reference the agent input or earlier outputs with `{"$ref": "<JSON pointer>"}`.
+//!
+//! ```json
+//! {
+//!   "input": { "text": "..." },
+//!   "steps": [
+//!     { "id": "short", "tool": "summarize", "input": { "text": { "$ref": "/input/text" } } },
+//!     { "id": "fr", "tool": "translate", "input": { "text": { "$ref": "/steps/short/output/summary" } } }
+//!   ],
+//!   "output": { "$ref": "/steps/fr/output" }
+//! }
+//! ```
+//!
+//! Pointers resolve against `{"input": ..., "steps": {"<id>": {"output": ...}}}`.
+//! Tools come from the project's lockfile and must be installed in `.agentpm/tools`.
+
+use super::{RunOptions, RunOutcome, ToolPackage};
+use crate::env::Redactor;
+use crate::history::{History, RunRecord};
+use crate::lockfile::Lockfile;
+use crate::manifest::{MANIFEST_FILE, Manifest};
+use crate::prelude::*;
+use crate::project::Project;
+use crate::util::schema_io::{compile_schema, schema_violations};
+use crate::util::serde_duration::as_millis;
+use anyhow::{anyhow, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+    time::Duration,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepFile {
+    /// Agent input used when none is given on the command line
+    #[serde(default)]
+    pub input: Option<Value>,
+    pub steps: Vec<Step>,
+    /// What the agent answers; defaults to the last step's output
+    #[serde(default)]
+    pub output: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    pub id: String,
+    pub tool: String,
+    /// Template for the tool input; `{"$ref": pointer}` objects are substituted
+    #[serde(default = "empty_object")]
+    pub input: Value,
+}
+
+fn empty_object() -> Value {
+    json!({})
+}
+
+/// What one step did.
+#[derive(Debug, Serialize)]
+pub struct StepTrace {
+    pub id: String,
+    pub tool: String,
+    pub version: String,
+    pub input: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(serialize_with = "as_millis", rename = "duration_ms")]
+    pub duration: Duration,
+    pub problems: Vec<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+}
+
+/// Result of a whole step file. Stops at the first failing step.
+#[derive(Debug)]
+pub struct AgentRun {
+    pub trace: Vec<StepTrace>,
+    /// The agent's answer, if every step succeeded
+    pub output: Option<Value>,
+    /// Problems with the agent input or output themselves (not a step's)
+    pub problems: Vec<String>,
+    /// Masks the secrets of every tool that ran
+    pub secrets: Redactor,
+}
+
+impl AgentRun {
+    pub fn ok(&self) -> bool {
+        self.problems.is_empty() && self.trace.iter().all(|s| s.problems.is_empty())
+    }
+}
+
+impl StepFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let file: StepFile = serde_json::from_str(&text)
+            .with_context(|| format!("{} is not a valid step file", path.display()))?;
+
+        let mut seen = BTreeSet::new();
+        for step in &file.steps {
+            if !seen.insert(step.id.as_str()) {
+                bail!("{}: step id `{}` is used twice", path.display(), step.id);
+            }
+        }
+        Ok(file)
+    }
+}
+
+/// Run `file`'s steps in order against `project`'s installed tools, recording each tool
+/// call in `history`. `on_step` sees each step as it finishes, with a redactor for the
+/// secrets of every tool run so far. Errors mean a step could not be started (unknown tool, bad `$ref`);
+/// a tool that ran and failed ends the run with its problems in the trace.
+pub fn run_steps(
+    project: &Project,
+    file: &StepFile,
+    input: Value,
+    opts: &RunOptions,
+    history: Option<&History>,
+    mut on_step: impl FnMut(&StepTrace, &Redactor),
+) -> Result<AgentRun> {
+    let tools = load_tools(project, file)?;
+    let mut run = AgentRun {
+        trace: Vec::new(),
+        output: None,
+        problems: Vec::new(),
+        secrets: Redactor::default(),
+    };
+
+    let manifest = &project.manifest;
+    if let Some(schema) = &manifest.inputs {
+        let schema = compile_schema(schema).context("`inputs` of the agent")?;
+        for v in schema_violations(&schema, &input) {
+            run.problems
+                .push(format!("input does not match the agent's `inputs`: {v}"));
+        }
+        if !run.problems.is_empty() {
+            return Ok(run);
+        }
+    }
+
+    let mut context = json!({ "input": input, "steps": {} });
+    for step in &file.steps {
+        let tool = &tools[&step.tool];
+        let input =
+            substitute(&step.input, &context).with_context(|| format!("step `{}`", step.id))?;
+        let mut trace = StepTrace {
+            id: step.id.clone(),
+            tool: step.tool.clone(),
+            version: tool.manifest.version.clone(),
+            input,
+            output: None,
+            duration: Duration::ZERO,
+            problems: Vec::new(),
+            stderr: String::new(),
+        };
+
+        let violations = tool.check_input(&trace.input);
+        if violations.is_empty() {
+            // Each tool is confined to its own permissions, not the agent's
+            let mut opts = opts.clone();
+            if let Some(sandbox) = &mut opts.sandbox {
+                sandbox.permissions = tool.manifest.permissions.clone();
+            }
+            let outcome = tool.run(&trace.input, &opts)?;
+            run.secrets.merge(&outcome.secrets);
+            if let Some(history) = history {
+                record(
+                    history,
+                    tool,
+                    &trace.input,
+                    &outcome,
+                    &run.secrets,
+                    &step.id,
+                );
+            }
+            trace.output = outcome.output;
+            trace.duration = outcome.duration;
+            trace.problems = outcome.problems;
+            trace.stderr = outcome.stderr;
+        } else {
+            trace.problems = violations
+                .into_iter()
+                .map(|v| format!("input does not match `inputs` of `{}`: {v}", step.tool))
+                .collect();
+        }
+
+        on_step(&trace, &run.secrets);
+        let failed = !trace.problems.is_empty();
+        context["steps"][&step.id] = json!({ "output": trace.output });
+        run.trace.push(trace);
+        if failed {
+            return Ok(run);
+        }
+    }
+
+    let output = match &file.output {
+        Some(template) => substitute(template, &context).context("step file `output`")?,
+        None => run
+            .trace
+            .last()
+            .and_then(|s| s.output.clone())
+            .unwrap_or(Value::Null),
+    };
+    if let Some(schema) = &manifest.outputs {
+        let schema = compile_schema(schema).context("`outputs` of the agent")?;
+        for v in schema_violations(&schema, &output) {
+            run.problems
+                .push(format!("output does not match the agent's `outputs`: {v}"));
+        }
+    }
+    run.output = Some(output);
+    Ok(run)
+}
+
+/// Save one step's tool call for `agentpm history` and `agentpm replay`. Steps pass values
+/// along, so the record is redacted with every secret seen so far, input included.
+fn record(
+    history: &History,
+    tool: &ToolPackage,
+    input: &Value,
+    outcome: &RunOutcome,
+    secrets: &Redactor,
+    step: &str,
+) {
+    let recorded = RunRecord::new(tool, input, outcome).and_then(|mut record| {
+        record.redact(secrets);
+        history.add(record)
+    });
+    match recorded {
+        Ok(id) => debug!("step `{step}` recorded as run #{id}"),
+        Err(e) => eprintln!("warning: could not record step `{step}` in the history: {e:#}"),
+    }
+}
+
+/// The installed packages of every tool the steps call, checked against the lockfile.
+fn load_tools(project: &Project, file: &StepFile) -> Result<BTreeMap<String, ToolPackage>> {
+    let lock = Lockfile::read(&project.lock_path())?.ok_or_else(|| {
+        anyhow!("No agentpm.lock found; the steps run installed tools. Try: `agentpm install`.")
+    })?;
+
+    let mut tools = BTreeMap::new();
+    for step in &file.steps {
+        if tools.contains_key(&step.tool) {
+            continue;
+        }
+        let Some(locked) = lock.get(&step.tool) else {
+            let known: Vec<&str> = lock.tools.iter().map(|t| t.name.as_str()).collect();
+            bail!(
+                "step `{}` calls `{}`, which is not in agentpm.lock (locked: {})",
+                step.id,
+                step.tool,
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            );
+        };
+        let dir = project.tool_dir(&locked.name)?;
+        let path = dir.join(MANIFEST_FILE);
+        if !path.is_file() {
+            bail!(
+                "`{}` is locked but not installed. Try: `agentpm install`.",
+                locked.name
+            );
+        }
+        let manifest = Manifest::load(&path)?;
+        if manifest.version != locked.version {
+            bail!(
+                "installed `{}` is {} but agentpm.lock wants {}. Try: `agentpm install`.",
+                locked.name,
+                manifest.version,
+                locked.version
+            );
+        }
+        tools.insert(step.tool.clone(), ToolPackage::new(dir, manifest)?);
+    }
+    Ok(tools)
+}
+
+/// Copy `template`, replacing every `{"$ref": pointer}` object by the value it points at.
+fn substitute(template: &Value, context: &Value) -> Result<Value> {
+    Ok(match template {
+        Value::Object(obj) if obj.len() == 1 && obj.contains_key("$ref") => {
+            let pointer = obj["$ref"]
+                .as_str()
+                .ok_or_else(|| anyhow!("`$ref` must be a JSON pointer string"))?;
+            context.pointer(pointer).cloned().ok_or_else(|| {
+                anyhow!("`$ref` {pointer} points at nothing (steps can only use earlier outputs)")
+            })?
+        }
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(k, v)| Ok((k.clone(), substitute(v, context)?)))
+                .collect::<Result<Map<_, _>>>()?,
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|v| substitute(v, context))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
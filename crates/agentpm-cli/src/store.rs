@@ -0,0 +1,81 @@
+use crate::prelude::*;
+use anyhow::{anyhow, bail};
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+use std::{fs, path::PathBuf};
+
+/// Content-addressed package store shared by every project:
+/// `<cache_dir>/store/sha256/<hex>/` holds the unpacked archive with that digest.
+pub struct Store {
+    root: PathBuf,
+}
+
+impl Store {
+    pub fn new(cfg: &Config) -> Self {
+        Self {
+            root: cfg.cache_dir.join("store"),
+        }
+    }
+
+    /// Where the package with `digest` lives (whether or not it's there yet).
+    pub fn path(&self, digest: &str) -> Result<PathBuf> {
+        let hex = sha256_hex(digest)?;
+        Ok(self.root.join("sha256").join(hex))
+    }
+
+    pub fn contains(&self, digest: &str) -> Result<bool> {
+        Ok(self.path(digest)?.is_dir())
+    }
+
+    /// Verify `archive` against `digest` and unpack it into the store.
+    pub fn add(&self, digest: &str, archive: &[u8]) -> Result<PathBuf> {
+        verify_digest(digest, archive)?;
+        let dest = self.path(digest)?;
+        if dest.is_dir() {
+            return Ok(dest);
+        }
+
+        // Unpack next to the destination, then rename, so a crash never leaves a half-filled entry
+        let tmp =
+            self.root
+                .join("tmp")
+                .join(format!("{}-{}", sha256_hex(digest)?, std::process::id()));
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(&tmp).with_context(|| format!("create {}", tmp.display()))?;
+        tar::Archive::new(GzDecoder::new(archive))
+            .unpack(&tmp)
+            .with_context(|| format!("unpacking archive {digest}"))?;
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+        }
+        if let Err(e) = fs::rename(&tmp, &dest) {
+            let _ = fs::remove_dir_all(&tmp);
+            // Another process may have won the race
+            if !dest.is_dir() {
+                return Err(e).with_context(|| format!("moving package into {}", dest.display()));
+            }
+        }
+        Ok(dest)
+    }
+}
+
+/// Check `bytes` against a "sha256:<hex>" digest.
+pub fn verify_digest(digest: &str, bytes: &[u8]) -> Result<()> {
+    let expected = sha256_hex(digest)?;
+    let actual = hex::encode(Sha256::digest(bytes));
+    if !actual.eq_ignore_ascii_case(expected) {
+        bail!("Digest mismatch: expected sha256:{expected}, got sha256:{actual}");
+    }
+    Ok(())
+}
+
+fn sha256_hex(digest: &str) -> Result<&str> {
+    let hex = digest
+        .strip_prefix("sha256:")
+        .ok_or_else(|| anyhow!("Unsupported digest `{digest}` (expected sha256:<hex>)"))?;
+    if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!("Malformed digest `{digest}`");
+    }
+    Ok(hex)
+}
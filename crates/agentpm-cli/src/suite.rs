@@ -0,0 +1,464 @@
+use crate::prelude::*;
+use crate::project::Project;
+use crate::runner::{RunOptions, ToolPackage};
+use crate::util::serde_duration::as_millis;
+use anyhow::{anyhow, bail};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Golden cases live in `<project>/tests/*.json`.
+pub const TESTS_DIR: &str = "tests";
+
+/// One input and what the tool must answer, from `tests/*.json` or the manifest's `examples`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCase {
+    /// Defaults to the file stem (or `examples[i]`)
+    #[serde(default)]
+    pub name: String,
+    pub input: Value,
+    /// Compared with the whole output per `match`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<Value>,
+    #[serde(default, rename = "match")]
+    pub matching: MatchMode,
+    /// Checks on parts of the output
+    #[serde(default, rename = "assert", skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+    /// Where the case came from, for reports
+    #[serde(skip)]
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// Output equals `expected`
+    #[default]
+    Exact,
+    /// Every member of `expected` is in the output (recursively); extra members are fine
+    Subset,
+}
+
+/// A check on the value at `path` (a JSON pointer like `/items/0/id`, or `$.items[0].id`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assertion {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<Value>,
+    /// Matched against the string (or, for other values, their JSON text)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exists: Option<bool>,
+}
+
+/// What happened to one case.
+#[derive(Debug, Serialize)]
+pub struct CaseResult {
+    pub name: String,
+    pub source: String,
+    pub ok: bool,
+    #[serde(serialize_with = "as_millis", rename = "duration_ms")]
+    pub duration: Duration,
+    pub failures: Vec<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+}
+
+/// Results of running a tool's cases.
+#[derive(Debug, Serialize)]
+pub struct SuiteReport {
+    pub tool: String,
+    pub passed: usize,
+    pub failed: usize,
+    #[serde(serialize_with = "as_millis", rename = "duration_ms")]
+    pub duration: Duration,
+    pub cases: Vec<CaseResult>,
+}
+
+/// Load `project`'s cases, keep those whose name contains `filter`, and run them in order.
+pub fn run_suite(
+    project: &Project,
+    filter: Option<&str>,
+    opts: &RunOptions,
+) -> Result<SuiteReport> {
+    let tool = ToolPackage::new(project.root.clone(), project.manifest.clone())?;
+    let started = Instant::now();
+    let cases: Vec<CaseResult> = load_cases(project)?
+        .iter()
+        .filter(|c| filter.is_none_or(|f| c.name.contains(f)))
+        .map(|case| run_case(&tool, case, opts))
+        .collect();
+    let passed = cases.iter().filter(|c| c.ok).count();
+    Ok(SuiteReport {
+        tool: tool.manifest.name.clone(),
+        passed,
+        failed: cases.len() - passed,
+        duration: started.elapsed(),
+        cases,
+    })
+}
+
+/// Every case of `project`: `tests/*.json` (sorted by file name), then the manifest's `examples`.
+/// A file holds one case or an array of cases.
+pub fn load_cases(project: &Project) -> Result<Vec<TestCase>> {
+    let mut cases = Vec::new();
+
+    let dir = project.root.join(TESTS_DIR);
+    if dir.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+            .with_context(|| format!("reading {}", dir.display()))?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .collect();
+        files.sort();
+        for file in files {
+            cases.extend(load_file(&project.root, &file)?);
+        }
+    }
+
+    for (i, example) in project.manifest.examples.iter().enumerate() {
+        let mut case: TestCase = serde_json::from_value(example.clone())
+            .with_context(|| format!("`examples[{i}]` in agent.json is not a valid test case"))?;
+        if case.name.is_empty() {
+            case.name = format!("examples[{i}]");
+        }
+        case.source = format!("agent.json#/examples/{i}");
+        cases.push(case);
+    }
+    Ok(cases)
+}
+
+fn load_file(root: &Path, file: &Path) -> Result<Vec<TestCase>> {
+    let text = fs::read_to_string(file).with_context(|| format!("reading {}", file.display()))?;
+    let value: Value = serde_json::from_str(&text)
+        .with_context(|| format!("{} is not valid JSON", file.display()))?;
+    let many = value.is_array();
+    let mut cases: Vec<TestCase> = match value {
+        Value::Array(items) => items
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<_, _>>(),
+        single => serde_json::from_value(single).map(|c| vec![c]),
+    }
+    .with_context(|| format!("{} is not a valid test case", file.display()))?;
+
+    let rel = file
+        .strip_prefix(root)
+        .unwrap_or(file)
+        .display()
+        .to_string();
+    let stem = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    for (i, case) in cases.iter_mut().enumerate() {
+        if case.name.is_empty() {
+            case.name = if many {
+                format!("{stem}[{i}]")
+            } else {
+                stem.clone()
+            };
+        }
+        case.source = if many {
+            format!("{rel}#/{i}")
+        } else {
+            rel.clone()
+        };
+    }
+    Ok(cases)
+}
+
+/// Run one case through the local runner and check the output.
+pub fn run_case(tool: &ToolPackage, case: &TestCase, opts: &RunOptions) -> CaseResult {
+    let started = Instant::now();
+    let mut result = CaseResult {
+        name: case.name.clone(),
+        source: case.source.clone(),
+        ok: false,
+        duration: Duration::ZERO,
+        failures: Vec::new(),
+        stderr: String::new(),
+    };
+
+    match tool.run(&case.input, opts) {
+        Err(e) => result.failures.push(format!("{e:#}")),
+        Ok(outcome) => {
+            result.failures.extend(outcome.problems.iter().cloned());
+            if let Some(output) = &outcome.output {
+                result.failures.extend(
+                    check(case, output)
+                        .into_iter()
+                        .map(|f| outcome.secrets.redact(&f)),
+                );
+            }
+            result.stderr = outcome.stderr;
+        }
+    }
+    result.duration = started.elapsed();
+    result.ok = result.failures.is_empty();
+    result
+}
+
+/// Everything wrong with `output` according to `case` (empty when it passes).
+pub fn check(case: &TestCase, output: &Value) -> Vec<String> {
+    let mut failures = Vec::new();
+    if let Some(expected) = &case.expected
+        && let Some(why) = mismatch(expected, output, case.matching, &mut String::new())
+    {
+        failures.push(why);
+    }
+    for assertion in &case.assertions {
+        if let Err(e) = assertion.check(output) {
+            failures.push(format!("{}: {e}", assertion.path));
+        }
+    }
+    failures
+}
+
+/// First difference between `expected` and `actual`, described with its pointer.
+fn mismatch(
+    expected: &Value,
+    actual: &Value,
+    mode: MatchMode,
+    path: &mut String,
+) -> Option<String> {
+    let at = |path: &str| {
+        if path.is_empty() {
+            "/".to_string()
+        } else {
+            path.to_string()
+        }
+    };
+    match (expected, actual) {
+        (Value::Object(want), Value::Object(got)) => {
+            for (key, want_value) in want {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                let found = match got.get(key) {
+                    Some(got_value) => mismatch(want_value, got_value, mode, path),
+                    None => Some(format!("at {path}: missing, expected {want_value}")),
+                };
+                path.truncate(len);
+                if found.is_some() {
+                    return found;
+                }
+            }
+            if mode == MatchMode::Exact
+                && let Some(extra) = got.keys().find(|k| !want.contains_key(*k))
+            {
+                return Some(format!("at {}: unexpected member `{extra}`", at(path)));
+            }
+            None
+        }
+        (Value::Array(want), Value::Array(got)) => {
+            if want.len() != got.len() {
+                return Some(format!(
+                    "at {}: expected {} item(s), got {}",
+                    at(path),
+                    want.len(),
+                    got.len()
+                ));
+            }
+            for (i, (w, g)) in want.iter().zip(got).enumerate() {
+                let len = path.len();
+                path.push_str(&format!("/{i}"));
+                let found = mismatch(w, g, mode, path);
+                path.truncate(len);
+                if found.is_some() {
+                    return found;
+                }
+            }
+            None
+        }
+        (want, got) if want == got => None,
+        (want, got) => Some(format!("at {}: expected {want}, got {got}", at(path))),
+    }
+}
+
+impl Assertion {
+    fn check(&self, output: &Value) -> Result<()> {
+        let pointer = to_pointer(&self.path)?;
+        let value = output.pointer(&pointer);
+
+        if let Some(exists) = self.exists {
+            match (exists, value) {
+                (true, None) => bail!("expected a value, found none"),
+                (false, Some(v)) => bail!("expected no value, found {v}"),
+                _ => {}
+            }
+        }
+        if self.equals.is_none() && self.regex.is_none() {
+            return Ok(());
+        }
+
+        let value = value.ok_or_else(|| anyhow!("no value at this path"))?;
+        if let Some(want) = &self.equals
+            && want != value
+        {
+            bail!("expected {want}, got {value}");
+        }
+        if let Some(pattern) = &self.regex {
+            let re = Regex::new(pattern).with_context(|| format!("invalid regex `{pattern}`"))?;
+            let text = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            if !re.is_match(&text) {
+                bail!("{value} does not match /{pattern}/");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Accept a JSON pointer as is, or turn a simple JSONPath (`$`, `.key`, `[0]`, `['key']`) into one.
+fn to_pointer(path: &str) -> Result<String> {
+    if path.is_empty() || path.starts_with('/') {
+        return Ok(path.to_string());
+    }
+    let Some(mut rest) = path.strip_prefix('$') else {
+        bail!("expected a JSON pointer (`/a/0`) or a JSONPath (`$.a[0]`)");
+    };
+    let escape = |key: &str| key.replace('~', "~0").replace('/', "~1");
+    let mut pointer = String::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                bail!("empty key in `{path}`");
+            }
+            pointer.push('/');
+            pointer.push_str(&escape(&after[..end]));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after
+                .find(']')
+                .ok_or_else(|| anyhow!("unclosed `[` in `{path}`"))?;
+            let inner = after[..end].trim();
+            let key = inner
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                .unwrap_or(inner);
+            pointer.push('/');
+            pointer.push_str(&escape(key));
+            rest = &after[end + 1..];
+        } else {
+            bail!("unsupported JSONPath `{path}` (use `.key`, `[0]` or `['key']`)");
+        }
+    }
+    Ok(pointer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn case(value: Value) -> TestCase {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn exact_and_subset_matching() {
+        let output = json!({ "items": [{ "id": 1, "tags": ["a"] }], "total": 1 });
+
+        let exact = case(json!({ "input": {}, "expected": output.clone() }));
+        assert!(check(&exact, &output).is_empty());
+        let exact =
+            case(json!({ "input": {}, "expected": { "items": [{ "id": 1, "tags": ["a"] }] } }));
+        assert_eq!(check(&exact, &output), ["at /: unexpected member `total`"]);
+
+        let subset = case(json!({
+            "input": {},
+            "expected": { "items": [{ "id": 1 }] },
+            "match": "subset",
+        }));
+        assert!(check(&subset, &output).is_empty());
+        let subset = case(json!({
+            "input": {},
+            "expected": { "items": [{ "id": 2 }, { "id": 3 }] },
+            "match": "subset",
+        }));
+        assert_eq!(
+            check(&subset, &output),
+            ["at /items: expected 2 item(s), got 1"]
+        );
+        let subset = case(json!({
+            "input": {},
+            "expected": { "items": [{ "id": "1" }], "a/b": null },
+            "match": "subset",
+        }));
+        assert_eq!(
+            check(&subset, &output),
+            ["at /a~1b: missing, expected null"]
+        );
+    }
+
+    #[test]
+    fn assertions_report_each_failure() {
+        let output = json!({ "summary": "Three lines.", "count": 3, "a.b": { "c": true } });
+        let passing = case(json!({
+            "input": {},
+            "assert": [
+                { "path": "$.summary", "regex": "^Three" },
+                { "path": "/count", "equals": 3, "regex": "^\\d+$" },
+                { "path": "$['a.b'].c", "equals": true },
+                { "path": "$.missing", "exists": false },
+            ],
+        }));
+        assert!(
+            check(&passing, &output).is_empty(),
+            "{:?}",
+            check(&passing, &output)
+        );
+
+        let failing = case(json!({
+            "input": {},
+            "assert": [
+                { "path": "$.summary", "regex": "^Four" },
+                { "path": "/count", "equals": 4 },
+                { "path": "$.missing", "exists": true },
+                { "path": "$.missing", "equals": 1 },
+                { "path": "$.count", "exists": false },
+                { "path": "count", "exists": true },
+            ],
+        }));
+        assert_eq!(
+            check(&failing, &output),
+            [
+                "$.summary: \"Three lines.\" does not match /^Four/",
+                "/count: expected 4, got 3",
+                "$.missing: expected a value, found none",
+                "$.missing: no value at this path",
+                "$.count: expected no value, found 3",
+                "count: expected a JSON pointer (`/a/0`) or a JSONPath (`$.a[0]`)",
+            ]
+        );
+    }
+
+    #[test]
+    fn jsonpath_becomes_a_pointer() {
+        for (path, pointer) in [
+            ("", ""),
+            ("/items/0", "/items/0"),
+            ("$", ""),
+            ("$.items[0].id", "/items/0/id"),
+            ("$['a/b'][\"c~d\"]", "/a~1b/c~0d"),
+            ("$[ 'spaced' ]", "/spaced"),
+        ] {
+            assert_eq!(to_pointer(path).unwrap(), pointer, "{path}");
+        }
+        for path in ["$..items", "$.items[0", "$items", "items"] {
+            assert!(to_pointer(path).is_err(), "{path}");
+        }
+    }
+}
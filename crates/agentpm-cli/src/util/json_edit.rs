@@ -0,0 +1,256 @@
+use super::json_span::{Node, Spanned};
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use std::ops::Range;
+
+/// Replace `range` of the source text with `replacement`.
+/// Edits touch only the bytes they must, so key order, spacing and indentation survive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// Apply non-overlapping edits (in any order).
+pub fn apply(text: &str, mut edits: Vec<TextEdit>) -> String {
+    edits.sort_by_key(|e| std::cmp::Reverse(e.range.start));
+    let mut out = text.to_string();
+    for e in edits {
+        out.replace_range(e.range, &e.replacement);
+    }
+    out
+}
+
+/// Replace a value in place.
+pub fn replace<T: Serialize>(text: &str, target: &Spanned, value: &T) -> Result<TextEdit> {
+    let style = Style::of(text);
+    let indent = style.pretty.then(|| line_indent(text, target.span.start));
+    Ok(TextEdit {
+        range: target.span.clone(),
+        replacement: style.render(value, indent)?,
+    })
+}
+
+/// Append `value` as the last item of `array`.
+pub fn push_item<T: Serialize>(text: &str, array: &Spanned, value: &T) -> Result<TextEdit> {
+    let style = Style::of(text);
+    let after = array
+        .items()
+        .last()
+        .map(|last| (last.span.start, last.span.end));
+    style.append(text, array, after, |indent| style.render(value, indent))
+}
+
+/// Add `"key": value` as the last member of `object`.
+pub fn push_member<T: Serialize>(
+    text: &str,
+    object: &Spanned,
+    key: &str,
+    value: &T,
+) -> Result<TextEdit> {
+    let style = Style::of(text);
+    let key = serde_json::to_string(key)?;
+    let after = match &object.node {
+        Node::Object(members) => members
+            .last()
+            .map(|last| (last.key_span.start, last.value.span.end)),
+        _ => None,
+    };
+    style.append(text, object, after, |indent| {
+        Ok(format!("{key}: {}", style.render(value, indent)?))
+    })
+}
+
+/// Remove the item at `index` of `array`, along with the separator it owned.
+pub fn remove_item(array: &Spanned, index: usize) -> TextEdit {
+    let items = array.items();
+    let range = if items.len() == 1 {
+        array.span.start + 1..array.span.end - 1
+    } else if index + 1 < items.len() {
+        items[index].span.start..items[index + 1].span.start
+    } else {
+        items[index - 1].span.end..items[index].span.end
+    };
+    TextEdit {
+        range,
+        replacement: String::new(),
+    }
+}
+
+/// Leading whitespace of the line containing byte `pos`.
+pub fn line_indent(text: &str, pos: usize) -> &str {
+    let line_start = text[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let line = &text[line_start..];
+    let width = line.len() - line.trim_start_matches([' ', '\t']).len();
+    &line[..width]
+}
+
+/// How the document is laid out: pretty (one entry per line) or compact, and its indent unit.
+struct Style {
+    pretty: bool,
+    unit: String,
+}
+
+impl Style {
+    fn of(text: &str) -> Self {
+        let unit = text
+            .lines()
+            .find_map(|l| {
+                let width = l.len() - l.trim_start_matches([' ', '\t']).len();
+                (width > 0 && !l.trim().is_empty()).then(|| l[..width].to_string())
+            })
+            .unwrap_or_else(|| "  ".to_string());
+        Self {
+            pretty: text.trim().contains('\n'),
+            unit,
+        }
+    }
+
+    /// Append an entry to a container. `after` is the (start, end) of the current last entry;
+    /// `render` gets the indentation to continue at, or `None` for compact output.
+    fn append(
+        &self,
+        text: &str,
+        container: &Spanned,
+        after: Option<(usize, usize)>,
+        render: impl FnOnce(Option<&str>) -> Result<String>,
+    ) -> Result<TextEdit> {
+        let replacement = match after {
+            // Follow the container's own layout: one entry per line, or inline
+            Some((start, _)) if text[container.span.clone()].contains('\n') => {
+                let indent = line_indent(text, start);
+                format!(",\n{indent}{}", render(Some(indent))?)
+            }
+            Some(_) => format!(", {}", render(None)?),
+            None if self.pretty => {
+                let outer = line_indent(text, container.span.start);
+                let inner = format!("{outer}{}", self.unit);
+                let open = &text[container.span.start..container.span.start + 1];
+                let close = &text[container.span.end - 1..container.span.end];
+                return Ok(TextEdit {
+                    range: container.span.clone(),
+                    replacement: format!(
+                        "{open}\n{inner}{}\n{outer}{close}",
+                        render(Some(&inner))?
+                    ),
+                });
+            }
+            None => render(None)?,
+        };
+        let at = match after {
+            Some((_, end)) => end,
+            None => container.span.start + 1,
+        };
+        Ok(TextEdit {
+            range: at..at,
+            replacement,
+        })
+    }
+
+    /// Pretty-print at `indent` with the document's unit, or compact when `indent` is `None`.
+    fn render<T: Serialize>(&self, value: &T, indent: Option<&str>) -> Result<String> {
+        let Some(indent) = indent else {
+            return Ok(serde_json::to_string(value)?);
+        };
+        let mut buf = Vec::new();
+        let mut ser = serde_json::Serializer::with_formatter(
+            &mut buf,
+            PrettyFormatter::with_indent(self.unit.as_bytes()),
+        );
+        value.serialize(&mut ser)?;
+        Ok(String::from_utf8(buf)?.replace('\n', &format!("\n{indent}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::json_span;
+    use serde_json::json;
+
+    fn tools(text: &str) -> Spanned {
+        json_span::parse(text)
+            .unwrap()
+            .pointer("/tools")
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn apply_takes_edits_in_any_order() {
+        let text = r#"{"a": 1, "b": 2}"#;
+        let edits = vec![
+            TextEdit {
+                range: 6..7,
+                replacement: "10".into(),
+            },
+            TextEdit {
+                range: 14..15,
+                replacement: "20".into(),
+            },
+            TextEdit {
+                range: 16..16,
+                replacement: " ".into(),
+            },
+        ];
+        assert_eq!(apply(text, edits), r#"{"a": 10, "b": 20} "#);
+    }
+
+    #[test]
+    fn replace_keeps_the_surrounding_layout() {
+        let text = "{\n  \"tools\": [\n    \"a@^1\"\n  ]\n}\n";
+        let target = tools(text).items()[0].clone();
+        let edit = replace(text, &target, &json!({ "name": "a", "version": "^1" })).unwrap();
+        assert_eq!(
+            apply(text, vec![edit]),
+            "{\n  \"tools\": [\n    {\n      \"name\": \"a\",\n      \"version\": \"^1\"\n    }\n  ]\n}\n"
+        );
+    }
+
+    #[test]
+    fn remove_item_drops_its_separator() {
+        let text = r#"{"tools": ["a", "b", "c"]}"#;
+        let cases = [
+            (0, r#"{"tools": ["b", "c"]}"#),
+            (1, r#"{"tools": ["a", "c"]}"#),
+            (2, r#"{"tools": ["a", "b"]}"#),
+        ];
+        for (index, expected) in cases {
+            let edit = remove_item(&tools(text), index);
+            assert_eq!(apply(text, vec![edit]), expected, "removing item {index}");
+        }
+
+        let text = "{\"tools\": [\n  \"a\"\n]}";
+        assert_eq!(
+            apply(text, vec![remove_item(&tools(text), 0)]),
+            "{\"tools\": []}"
+        );
+    }
+
+    #[test]
+    fn push_then_remove_round_trips() {
+        for text in [
+            r#"{"tools": ["a"]}"#,
+            "{\n  \"tools\": [\n    \"a\"\n  ]\n}\n",
+            "{\n  \"tools\": []\n}\n",
+        ] {
+            let pushed = apply(text, vec![push_item(text, &tools(text), &"b").unwrap()]);
+            let array = tools(&pushed);
+            assert_eq!(array.items().len(), tools(text).items().len() + 1);
+            let last = array.items().len() - 1;
+            assert_eq!(apply(&pushed, vec![remove_item(&array, last)]), text);
+        }
+    }
+
+    #[test]
+    fn push_member_follows_the_document_style() {
+        let text = "{\n    \"name\": \"a\"\n}\n";
+        let object = json_span::parse(text).unwrap();
+        let edit = push_member(text, &object, "version", &"1.0.0").unwrap();
+        assert_eq!(
+            apply(text, vec![edit]),
+            "{\n    \"name\": \"a\",\n    \"version\": \"1.0.0\"\n}\n"
+        );
+    }
+}
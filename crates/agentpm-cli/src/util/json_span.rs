@@ -0,0 +1,256 @@
+use anyhow::{Result, bail};
+use std::ops::Range;
+
+/// A JSON value plus the byte range it occupies in the source text.
+/// serde_json throws positions away; edits and diagnostics need them.
+#[derive(Debug, Clone)]
+pub struct Spanned {
+    pub span: Range<usize>,
+    pub node: Node,
+}
+
+#[derive(Debug, Clone)]
+pub enum Node {
+    Object(Vec<Member>),
+    Array(Vec<Spanned>),
+    String(String),
+    Number,
+    Bool,
+    Null,
+}
+
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub key: String,
+    pub key_span: Range<usize>,
+    pub value: Spanned,
+}
+
+pub fn parse(text: &str) -> Result<Spanned> {
+    let mut p = Parser {
+        src: text.as_bytes(),
+        pos: 0,
+    };
+    p.skip_ws();
+    let value = p.value()?;
+    p.skip_ws();
+    if p.pos != p.src.len() {
+        bail!("trailing characters at byte {}", p.pos);
+    }
+    Ok(value)
+}
+
+impl Spanned {
+    pub fn get(&self, key: &str) -> Option<&Spanned> {
+        self.member(key).map(|m| &m.value)
+    }
+
+    /// The member named `key`; the last one if it repeats, as that is the one serde keeps.
+    pub fn member(&self, key: &str) -> Option<&Member> {
+        match &self.node {
+            Node::Object(members) => members.iter().rfind(|m| m.key == key),
+            _ => None,
+        }
+    }
+
+    pub fn items(&self) -> &[Spanned] {
+        match &self.node {
+            Node::Array(items) => items,
+            _ => &[],
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.node {
+            Node::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The value a JSON pointer (`/a/0/b`) refers to, like `serde_json::Value::pointer`.
+    pub fn pointer(&self, pointer: &str) -> Option<&Spanned> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        let mut target = self;
+        for token in pointer.strip_prefix('/')?.split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            target = match &target.node {
+                Node::Object(_) => target.get(&token)?,
+                Node::Array(items) => items.get(token.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+}
+
+/// 1-based line and column (in characters) of byte `offset` in `text`.
+pub fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<Spanned> {
+        let start = self.pos;
+        let node = match self.peek() {
+            Some(b'{') => self.object()?,
+            Some(b'[') => self.array()?,
+            Some(b'"') => Node::String(self.string()?),
+            Some(b't') => self.literal("true", Node::Bool)?,
+            Some(b'f') => self.literal("false", Node::Bool)?,
+            Some(b'n') => self.literal("null", Node::Null)?,
+            Some(b'-' | b'0'..=b'9') => {
+                while matches!(
+                    self.peek(),
+                    Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+                ) {
+                    self.pos += 1;
+                }
+                Node::Number
+            }
+            Some(c) => bail!("unexpected `{}` at byte {}", c as char, self.pos),
+            None => bail!("unexpected end of input"),
+        };
+        Ok(Spanned {
+            span: start..self.pos,
+            node,
+        })
+    }
+
+    fn object(&mut self) -> Result<Node> {
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Node::Object(members));
+        }
+        loop {
+            self.skip_ws();
+            let key_start = self.pos;
+            let key = self.string()?;
+            let key_span = key_start..self.pos;
+            self.skip_ws();
+            self.expect(b':')?;
+            self.skip_ws();
+            let value = self.value()?;
+            members.push(Member {
+                key,
+                key_span,
+                value,
+            });
+            self.skip_ws();
+            match self.next() {
+                Some(b',') => continue,
+                Some(b'}') => return Ok(Node::Object(members)),
+                _ => bail!(
+                    "expected `,` or `}}` at byte {}",
+                    self.pos.saturating_sub(1)
+                ),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Node> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Node::Array(items));
+        }
+        loop {
+            self.skip_ws();
+            items.push(self.value()?);
+            self.skip_ws();
+            match self.next() {
+                Some(b',') => continue,
+                Some(b']') => return Ok(Node::Array(items)),
+                _ => bail!("expected `,` or `]` at byte {}", self.pos.saturating_sub(1)),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let start = self.pos;
+        self.expect(b'"')?;
+        loop {
+            match self.next() {
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    self.next();
+                }
+                Some(_) => {}
+                None => bail!("unterminated string starting at byte {start}"),
+            }
+        }
+        // Let serde_json deal with escapes
+        let raw = std::str::from_utf8(&self.src[start..self.pos])?;
+        Ok(serde_json::from_str(raw)?)
+    }
+
+    fn literal(&mut self, word: &str, node: Node) -> Result<Node> {
+        if self.src[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(node)
+        } else {
+            bail!("invalid literal at byte {}", self.pos)
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<()> {
+        if self.next() != Some(c) {
+            bail!(
+                "expected `{}` at byte {}",
+                c as char,
+                self.pos.saturating_sub(1)
+            );
+        }
+        Ok(())
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_keys_resolve_to_the_value_serde_keeps() {
+        let text = r#"{ "name": "first", "tools": [{ "a": 1, "a": 2 }], "name": "last" }"#;
+        let doc = parse(text).unwrap();
+        let value: serde_json::Value = serde_json::from_str(text).unwrap();
+
+        assert_eq!(doc.get("name").and_then(Spanned::as_str), Some("last"));
+        let name = doc.member("name").unwrap();
+        assert_eq!(&text[name.key_span.clone()], r#""name""#);
+        assert_eq!(&text[name.value.span.clone()], r#""last""#);
+
+        let a = doc.pointer("/tools/0/a").unwrap();
+        assert_eq!(&text[a.span.clone()], value["tools"][0]["a"].to_string());
+    }
+}
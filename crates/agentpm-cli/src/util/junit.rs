@@ -0,0 +1,108 @@
+use std::{fmt::Write, time::Duration};
+
+/// One `<testsuite>` of a JUnit XML report (the format CI systems ingest).
+#[derive(Debug, Default)]
+pub struct Suite {
+    pub name: String,
+    pub cases: Vec<Case>,
+}
+
+#[derive(Debug, Default)]
+pub struct Case {
+    pub name: String,
+    pub classname: String,
+    pub time: Duration,
+    /// `(message, details)` of each failure; none means the case passed
+    pub failures: Vec<(String, String)>,
+    /// Captured output, attached as `<system-err>`
+    pub stderr: String,
+}
+
+/// Render `suites` as a JUnit XML document.
+pub fn render(suites: &[Suite]) -> String {
+    let count = |f: fn(&Case) -> bool| -> usize {
+        suites
+            .iter()
+            .flat_map(|s| &s.cases)
+            .filter(|c| f(c))
+            .count()
+    };
+    let total_time: f64 = suites
+        .iter()
+        .flat_map(|s| &s.cases)
+        .map(|c| c.time.as_secs_f64())
+        .sum();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<testsuites tests=\"{}\" failures=\"{}\" time=\"{total_time:.3}\">",
+        count(|_| true),
+        count(|c| !c.failures.is_empty())
+    );
+    for suite in suites {
+        let failures = suite
+            .cases
+            .iter()
+            .filter(|c| !c.failures.is_empty())
+            .count();
+        let time: f64 = suite.cases.iter().map(|c| c.time.as_secs_f64()).sum();
+        let _ = writeln!(
+            out,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" time=\"{time:.3}\">",
+            escape(&suite.name),
+            suite.cases.len()
+        );
+        for case in &suite.cases {
+            let _ = write!(
+                out,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape(&case.name),
+                escape(&case.classname),
+                case.time.as_secs_f64()
+            );
+            if case.failures.is_empty() && case.stderr.is_empty() {
+                out.push_str("/>\n");
+                continue;
+            }
+            out.push_str(">\n");
+            for (message, details) in &case.failures {
+                let _ = writeln!(
+                    out,
+                    "      <failure message=\"{}\">{}</failure>",
+                    escape(message),
+                    escape(details)
+                );
+            }
+            if !case.stderr.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "      <system-err>{}</system-err>",
+                    escape(&case.stderr)
+                );
+            }
+            out.push_str("    </testcase>\n");
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+/// Escape text for XML attributes and content, dropping characters XML 1.0 forbids.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' | '\r' | '\t' => out.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
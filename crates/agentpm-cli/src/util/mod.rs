@@ -1,2 +1,6 @@
+pub mod json_edit;
+pub mod json_span;
+pub mod junit;
 pub mod schema_io;
+pub mod serde_duration;
 pub use schema_io::{discover_manifest_files, load_json, load_schema_value};
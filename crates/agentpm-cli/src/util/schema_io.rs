@@ -1,4 +1,5 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
+use jsonschema::{Draft, JSONSchema};
 use serde_json::Value;
 use std::{
     fs,
@@ -16,6 +17,28 @@ pub fn load_schema_value(source: &str) -> Result<Value> {
     }
 }
 
+/// Compile a JSON Schema (draft 2020-12) that lives inside a manifest, e.g. `inputs`.
+pub fn compile_schema(schema: &Value) -> Result<JSONSchema> {
+    JSONSchema::options()
+        .with_draft(Draft::Draft202012)
+        .compile(schema)
+        .map_err(|e| anyhow!("invalid JSON Schema: {e}"))
+}
+
+/// Every violation of `schema` by `value`, as "<instance path>: <message>".
+pub fn schema_violations(schema: &JSONSchema, value: &Value) -> Vec<String> {
+    match schema.validate(value) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|e| {
+                let path = e.instance_path.to_string();
+                let path = if path.is_empty() { "/".into() } else { path };
+                format!("{path}: {e}")
+            })
+            .collect(),
+    }
+}
+
 /// Returns (value, raw_string) for potential rewriting on --fix
 pub fn load_json(path: &Path) -> Result<(serde_json::Value, String)> {
     let raw =
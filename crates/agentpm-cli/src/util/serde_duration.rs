@@ -0,0 +1,6 @@
+use std::time::Duration;
+
+/// Serialize a `Duration` as whole milliseconds: `#[serde(serialize_with = "...")]`.
+pub fn as_millis<S: serde::Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u64(d.as_millis() as u64)
+}
@@ -1,7 +1,15 @@
 use crate::error::{ApiErrorBody, Result, SdkError};
-use reqwest::Client;
+use crate::types::{Page, ToolVersion};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use reqwest::{Client, Response};
 use std::time::Duration;
 
+/// Total time allowed for an API call
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Downloads may take as long as they need while data keeps arriving
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Clone)]
 pub struct AgentPmClient {
     http: Client,
@@ -12,7 +20,8 @@ impl AgentPmClient {
     pub fn new(base_url: impl Into<String>) -> Result<Self> {
         Ok(Self {
             http: Client::builder()
-                .timeout(Duration::from_secs(10))
+                .connect_timeout(CONNECT_TIMEOUT)
+                .read_timeout(READ_TIMEOUT)
                 .user_agent("agentpm-cli/0.1")
                 .build()?,
             base_url: base_url.into(),
@@ -21,9 +30,8 @@ impl AgentPmClient {
 
     /// Minimal example: GET /whoami -> String (replace with types::User later)
     pub async fn whoami(&self) -> Result<String> {
-        let url = format!("{}/whoami", self.base_url.trim_end_matches('/'));
-        let resp = self.http.get(url).send().await?;
-        let status = resp.status(); // capture before consuming body
+        let url = format!("{}/whoami", self.base_url());
+        let resp = self.http.get(url).timeout(REQUEST_TIMEOUT).send().await?;
 
         if resp.status().is_success() {
             // TODO: Replace with `types::User` if your API returns structured JSON
@@ -31,32 +39,107 @@ impl AgentPmClient {
             return Ok(txt);
         }
 
-        // Map common statuses
-        match resp.status().as_u16() {
-            401 => return Err(SdkError::Unauthorized),
-            404 => return Err(SdkError::NotFound),
-            429 => {
-                let retry_after = resp
-                    .headers()
-                    .get("retry-after")
-                    .and_then(|h| h.to_str().ok())
-                    .and_then(|s| s.parse::<u64>().ok());
-                return Err(SdkError::RateLimited { retry_after });
-            }
-            _ => {}
+        Err(error_from_response(resp).await)
+    }
+
+    /// GET /tools/{name}/versions -> every published version of a tool
+    pub async fn tool_versions(&self, name: &str) -> Result<Vec<ToolVersion>> {
+        let url = format!("{}/tools/{}/versions", self.base_url(), path_segment(name)?);
+        let resp = self.http.get(url).timeout(REQUEST_TIMEOUT).send().await?;
+
+        if resp.status().is_success() {
+            let page: Page<ToolVersion> = resp.json().await?;
+            return Ok(page.items);
+        }
+
+        Err(error_from_response(resp).await)
+    }
+
+    /// GET an absolute URL (e.g. `ToolVersion::archive_url`) and return the raw body.
+    /// Only stalls time out, so large archives can finish over slow links.
+    pub async fn download(&self, url: &str) -> Result<Vec<u8>> {
+        let resp = self.http.get(url).send().await?;
+
+        if resp.status().is_success() {
+            return Ok(resp.bytes().await?.to_vec());
+        }
+
+        Err(error_from_response(resp).await)
+    }
+
+    /// Base URL without a trailing slash (also recorded as the lockfile `source`)
+    pub fn base_url(&self) -> &str {
+        self.base_url.trim_end_matches('/')
+    }
+}
+
+/// Characters left as is in a path segment: RFC 3986 unreserved ones
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// `value` escaped as one path segment, so it can't reach another endpoint
+fn path_segment(value: &str) -> Result<String> {
+    // Dot segments are resolved away by URL parsers, even when escaped
+    if matches!(value, "" | "." | "..") {
+        return Err(SdkError::Other(format!(
+            "{value:?} can't be used in a registry URL"
+        )));
+    }
+    Ok(utf8_percent_encode(value, SEGMENT).to_string())
+}
+
+/// Map a non-2xx response to an `SdkError`
+async fn error_from_response(resp: Response) -> SdkError {
+    let status = resp.status(); // capture before consuming body
+
+    // Map common statuses
+    match status.as_u16() {
+        401 => return SdkError::Unauthorized,
+        404 => return SdkError::NotFound,
+        429 => {
+            let retry_after = resp
+                .headers()
+                .get("retry-after")
+                .and_then(|h| h.to_str().ok())
+                .and_then(|s| s.parse::<u64>().ok());
+            return SdkError::RateLimited { retry_after };
         }
+        _ => {}
+    }
 
-        // Try to parse a structured error body; fall back to plain text
-        let bytes = resp.bytes().await?;
-        if !bytes.is_empty() {
-            if let Ok(body) = serde_json::from_slice::<ApiErrorBody>(&bytes) {
-                return Err(SdkError::Api(body));
-            }
-            if let Ok(txt) = String::from_utf8(bytes.to_vec()) {
-                return Err(SdkError::Other(txt));
-            }
+    // Try to parse a structured error body; fall back to plain text
+    let bytes = match resp.bytes().await {
+        Ok(b) => b,
+        Err(e) => return SdkError::Http(e),
+    };
+    if !bytes.is_empty() {
+        if let Ok(body) = serde_json::from_slice::<ApiErrorBody>(&bytes) {
+            return SdkError::Api(body);
         }
+        if let Ok(txt) = String::from_utf8(bytes.to_vec()) {
+            return SdkError::Other(txt);
+        }
+    }
 
-        Err(SdkError::Other(format!("HTTP {}", status)))
+    SdkError::Other(format!("HTTP {}", status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_stay_in_their_path_segment() {
+        assert_eq!(path_segment("summarize").unwrap(), "summarize");
+        assert_eq!(path_segment("a.b_c-d~e").unwrap(), "a.b_c-d~e");
+        assert_eq!(path_segment("../admin").unwrap(), "..%2Fadmin");
+        assert_eq!(path_segment("x?y=1#z").unwrap(), "x%3Fy%3D1%23z");
+        assert_eq!(path_segment("%2e%2e").unwrap(), "%252e%252e");
+        for dots in ["", ".", ".."] {
+            assert!(path_segment(dots).is_err(), "{dots:?}");
+        }
     }
 }
@@ -20,6 +20,30 @@ pub struct Tool {
     pub outputs: Option<serde_json::Value>,
 }
 
+/// A published version of a tool (or agent) in the registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolVersion {
+    pub name: String,
+    pub version: String,
+    /// Digest of the package archive, e.g. "sha256:<hex>"
+    pub digest: String,
+    /// Where to download the package archive (.tar.gz)
+    pub archive_url: String,
+    /// Tools this version composes (only agents have any)
+    #[serde(default)]
+    pub dependencies: Vec<ToolDependency>,
+    #[serde(default)]
+    pub yanked: bool,
+}
+
+/// A `tools` entry of a published agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDependency {
+    pub name: String,
+    /// Semver range; `None` means any version
+    pub version: Option<String>,
+}
+
 /// Status of a tool run
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]